@@ -19,15 +19,15 @@ command_exists() {
 }
 
 # Check and install rust
+# setcyrup manages the toolchain itself, so only install one when missing
 install_rust() {
+    local channel="${CYRUP_RUST_CHANNEL:-stable}"
     if ! command_exists rustup; then
-        log_info "Installing Rust via rustup..."
-        curl --proto '=https' --tlsv1.2 -sSf https://sh.rustup.rs | sh -s -- -y --default-toolchain nightly
+        log_info "Installing Rust ($channel) via rustup..."
+        curl --proto '=https' --tlsv1.2 -sSf https://sh.rustup.rs | sh -s -- -y --profile minimal --default-toolchain "$channel"
         source "$HOME/.cargo/env"
     else
-        log_info "Rust is already installed, updating to nightly..."
-        rustup default nightly
-        rustup update
+        log_info "Rust is already installed, leaving the default toolchain alone"
     fi
 }
 
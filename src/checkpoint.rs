@@ -0,0 +1,144 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::steps::rust::RustSpec;
+
+/// The selection an install run was started with, so it can be resumed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedPlan {
+    pub target: Option<String>,
+    pub components: Vec<String>,
+    /// Accelerator variant, e.g. "cuda"; absent in checkpoints from older versions
+    #[serde(default)]
+    pub accelerator: Option<String>,
+    #[serde(default)]
+    pub pytorch_index: Option<String>,
+    #[serde(default)]
+    pub rust: RustSpec,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CheckpointFile {
+    plan: Option<SavedPlan>,
+    completed: BTreeMap<String, DateTime<Utc>>,
+}
+
+/// Completion checkpoints for install steps, persisted after every step
+#[derive(Debug)]
+pub struct CheckpointStore {
+    path: PathBuf,
+    data: CheckpointFile,
+}
+
+impl CheckpointStore {
+    /// Default location, `~/.local/state/cyrup/checkpoints.json`
+    pub fn default_path() -> Result<PathBuf> {
+        let home = home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+        Ok(home.join(".local/state/cyrup/checkpoints.json"))
+    }
+
+    /// Load the checkpoints left by an earlier run, if any
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = if path.exists() {
+            let contents = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Corrupt checkpoint file {}", path.display()))?
+        } else {
+            CheckpointFile::default()
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            data,
+        })
+    }
+
+    /// Start a fresh run for `plan`, discarding earlier checkpoints
+    pub fn fresh(path: &Path, plan: SavedPlan) -> Result<Self> {
+        let store = Self {
+            path: path.to_path_buf(),
+            data: CheckpointFile {
+                plan: Some(plan),
+                completed: BTreeMap::new(),
+            },
+        };
+        store.save()?;
+        Ok(store)
+    }
+
+    /// The plan recorded by the run being resumed
+    pub fn plan(&self) -> Option<&SavedPlan> {
+        self.data.plan.as_ref()
+    }
+
+    /// Whether the step with `id` completed in an earlier run
+    pub fn is_complete(&self, id: &str) -> bool {
+        self.data.completed.contains_key(id)
+    }
+
+    /// Mark a step as completed and persist immediately
+    pub fn mark_complete(&mut self, id: &str) -> Result<()> {
+        self.data.completed.insert(id.to_string(), Utc::now());
+        self.save()
+    }
+
+    /// Forget a step whose postcondition no longer holds
+    pub fn invalidate(&mut self, id: &str) -> Result<()> {
+        if self.data.completed.remove(id).is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Write atomically so an interrupted run never leaves a torn file
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&self.data)?)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to write {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoints_survive_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state/checkpoints.json");
+        let plan = SavedPlan {
+            target: Some("Host".to_string()),
+            components: vec!["Cyrup Developer".to_string()],
+            accelerator: Some("cpu".to_string()),
+            pytorch_index: None,
+            rust: RustSpec::default(),
+        };
+
+        let mut store = CheckpointStore::fresh(&path, plan.clone()).unwrap();
+        store.mark_complete("rust.rustup").unwrap();
+        store.mark_complete("rust.crate.hurl").unwrap();
+        store.invalidate("rust.crate.hurl").unwrap();
+
+        let store = CheckpointStore::load(&path).unwrap();
+        assert_eq!(store.plan(), Some(&plan));
+        assert!(store.is_complete("rust.rustup"));
+        assert!(!store.is_complete("rust.crate.hurl"));
+
+        // A fresh run forgets everything from before
+        let store = CheckpointStore::fresh(&path, plan).unwrap();
+        assert!(!store.is_complete("rust.rustup"));
+    }
+}
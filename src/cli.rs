@@ -0,0 +1,160 @@
+use anyhow::Result;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use crate::logging::Level;
+use crate::steps::rust::{Channel, RustSpec};
+
+/// System installation and management for Cyrup AI
+#[derive(Debug, Parser)]
+#[command(name = "setcyrup", version, about, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    /// Level of detail written to the run log (error, warn, info, debug, trace)
+    #[arg(long, global = true, env = "CYRUP_LOG", default_value = "info")]
+    pub log_level: Level,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Options for `install`, which runs when no command is given
+    #[command(flatten)]
+    pub install: InstallArgs,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Select and install components (the default)
+    Install(InstallArgs),
+    /// Show the log of the most recent run
+    Logs,
+    /// Report hardware, OS and GPU details
+    Inventory(InventoryArgs),
+    /// Diagnose common environment problems
+    Doctor(DoctorArgs),
+    /// Remove what the last install put in the home directory
+    Uninstall(UninstallArgs),
+    /// Write the last install plan as a container build recipe
+    Export(ExportArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct InstallArgs {
+    /// Continue the last run, skipping steps that already succeeded
+    #[arg(long)]
+    pub resume: bool,
+
+    /// Maximum number of steps to run in parallel
+    #[arg(long, short, env = "CYRUP_JOBS", default_value_t = 4)]
+    pub jobs: usize,
+
+    /// Start without checking disk space, permissions and required tools
+    #[arg(long)]
+    pub skip_preflight: bool,
+
+    /// Never ask for root; skip system packages and install only what
+    /// lives in the home directory
+    #[arg(long)]
+    pub user_only: bool,
+
+    /// Default Rust toolchain, e.g. stable, nightly-2024-11-28 or 1.82.0
+    #[arg(long, env = "CYRUP_RUST_TOOLCHAIN")]
+    pub rust_toolchain: Option<Channel>,
+
+    /// More Rust toolchains to install, comma separated
+    #[arg(long, env = "CYRUP_RUST_EXTRA_TOOLCHAINS", value_delimiter = ',')]
+    pub rust_extra_toolchains: Vec<Channel>,
+
+    /// Rust components to add instead of the defaults, comma separated
+    #[arg(long, env = "CYRUP_RUST_COMPONENTS", value_delimiter = ',')]
+    pub rust_components: Option<Vec<String>>,
+
+    /// Rust targets to add instead of the defaults, comma separated
+    #[arg(long, env = "CYRUP_RUST_TARGETS", value_delimiter = ',')]
+    pub rust_targets: Option<Vec<String>>,
+}
+
+impl InstallArgs {
+    /// Rust spec for a new selection: the defaults with the flags applied
+    pub fn rust_spec(&self) -> Result<RustSpec> {
+        let mut spec = RustSpec::default();
+        if let Some(channel) = &self.rust_toolchain {
+            spec.channel = channel.clone();
+        }
+        spec.extra_toolchains = self.rust_extra_toolchains.clone();
+        if let Some(components) = &self.rust_components {
+            spec.components = components.clone();
+        }
+        if let Some(targets) = &self.rust_targets {
+            spec.targets = targets.clone();
+        }
+        spec.validate()?;
+        Ok(spec)
+    }
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct InventoryArgs {
+    /// Print machine readable JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct DoctorArgs {
+    /// Repair the problems that are safe to fix automatically
+    #[arg(long)]
+    pub fix: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct UninstallArgs {
+    /// Do not ask for confirmation
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ExportArgs {
+    /// Recipe format to write
+    #[arg(long, value_enum, default_value_t = ExportFormat::Dockerfile)]
+    pub format: ExportFormat,
+
+    /// File to write instead of standard output
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Layered Dockerfile, also usable with Buildah and other OCI builders
+    Dockerfile,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_spec_from_flags() {
+        let cli = Cli::try_parse_from([
+            "setcyrup",
+            "--rust-toolchain",
+            "nightly-2024-11-28",
+            "--rust-extra-toolchains",
+            "stable,1.82.0",
+            "--rust-components",
+            "clippy,miri",
+        ])
+        .unwrap();
+        let spec = cli.install.rust_spec().unwrap();
+        assert_eq!(spec.channel, Channel::Pinned("nightly-2024-11-28".to_string()));
+        assert_eq!(spec.extra_toolchains, vec![Channel::Stable, Channel::Pinned("1.82.0".to_string())]);
+        assert_eq!(spec.components, vec!["clippy", "miri"]);
+        assert_eq!(spec.targets, RustSpec::default().targets);
+
+        // miri needs nightly
+        let cli = Cli::try_parse_from(["setcyrup", "--rust-components", "miri"]).unwrap();
+        assert!(cli.install.rust_spec().is_err());
+        assert!(Cli::try_parse_from(["setcyrup", "--rust-toolchain", "latest"]).is_err());
+    }
+}
@@ -0,0 +1,258 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::dockerfile;
+use crate::menu::{InstallPlan, Service, Toolchain};
+
+/// Comment at the top of every file setcyrup writes
+const MARKER: &str = "Generated by setcyrup";
+
+/// Whether setcyrup wrote `contents`, from the marker in its first two lines
+///
+/// The Dockerfile needs its `# syntax` line first, so the marker is second.
+fn is_generated(contents: &str) -> bool {
+    contents
+        .lines()
+        .take(2)
+        .any(|line| line.trim_start_matches(['#', '/']).trim_start().starts_with(MARKER))
+}
+
+/// VS Code extensions and forwarded ports per toolchain
+fn toolchain_extras(toolchain: &Toolchain) -> (&'static [&'static str], &'static [u16]) {
+    match toolchain {
+        // dx serve
+        Toolchain::Rust => (&["rust-lang.rust-analyzer", "tamasfe.even-better-toml"], &[8080]),
+        Toolchain::Python => (&["ms-python.python", "charliermarsh.ruff"], &[]),
+        // JupyterLab
+        Toolchain::Ml => (&["ms-toolsai.jupyter"], &[8888]),
+        Toolchain::Node => (&["dbaeumer.vscode-eslint"], &[3000]),
+        Toolchain::Go => (&["golang.go"], &[]),
+        Toolchain::Releases => (&[], &[]),
+    }
+}
+
+fn services(plan: &InstallPlan) -> Vec<Service> {
+    let mut services = Vec::new();
+    for service in plan.components.iter().flat_map(|component| &component.services) {
+        if !services.contains(service) {
+            services.push(*service);
+        }
+    }
+    services
+}
+
+fn project_name(project: &Path) -> String {
+    project
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "workspace".to_string())
+}
+
+/// `devcontainer.json`, using the compose file when there are services
+fn devcontainer_json(plan: &InstallPlan, project: &str) -> Value {
+    let mut extensions: Vec<&str> = Vec::new();
+    let mut ports: Vec<Value> = Vec::new();
+    for toolchain in plan.components.iter().flat_map(|component| &component.toolchains) {
+        let (toolchain_extensions, toolchain_ports) = toolchain_extras(toolchain);
+        for extension in toolchain_extensions {
+            if !extensions.contains(extension) {
+                extensions.push(extension);
+            }
+        }
+        for port in toolchain_ports {
+            if !ports.contains(&json!(port)) {
+                ports.push(json!(port));
+            }
+        }
+    }
+
+    let services = services(plan);
+    for service in &services {
+        if *service == Service::Postgres {
+            extensions.extend(["mtxr.sqltools", "mtxr.sqltools-driver-pg"]);
+        }
+        ports.push(json!(format!("db:{}", service.port())));
+    }
+
+    let names: Vec<&str> = plan.components.iter().map(|c| c.name.as_str()).collect();
+    let mut config = json!({
+        "name": format!("{} ({})", project, names.join(", ")),
+        "remoteUser": dockerfile::USER,
+        "customizations": { "vscode": { "extensions": extensions } },
+        "forwardPorts": ports,
+    });
+    if services.is_empty() {
+        config["build"] = json!({ "dockerfile": "Dockerfile" });
+    } else {
+        config["dockerComposeFile"] = json!("docker-compose.yml");
+        config["service"] = json!("app");
+        config["workspaceFolder"] = json!(format!("/workspaces/{}", project));
+    }
+    config
+}
+
+/// Compose file running the services next to the dev container
+fn compose(services: &[Service], project: &str) -> String {
+    let mut compose = format!(
+        "# {}\n\
+         services:\n\
+         \x20 app:\n\
+         \x20   build:\n\
+         \x20     context: .\n\
+         \x20     dockerfile: Dockerfile\n\
+         \x20   volumes:\n\
+         \x20     - ..:/workspaces/{}:cached\n\
+         \x20   command: sleep infinity\n",
+        MARKER, project
+    );
+    if services.contains(&Service::Postgres) {
+        compose.push_str(
+            "    environment:\n\
+             \x20     DATABASE_URL: postgres://postgres:postgres@db:5432/postgres\n",
+        );
+    }
+    compose.push_str("    depends_on:\n      - db\n");
+
+    for service in services {
+        match service {
+            Service::Postgres => compose.push_str(&format!(
+                "\n  db:\n\
+                 \x20   image: {}\n\
+                 \x20   restart: unless-stopped\n\
+                 \x20   environment:\n\
+                 \x20     POSTGRES_USER: postgres\n\
+                 \x20     POSTGRES_PASSWORD: postgres\n\
+                 \x20   volumes:\n\
+                 \x20     - postgres-data:/var/lib/postgresql/data\n",
+                service.image()
+            )),
+        }
+    }
+    if services.contains(&Service::Postgres) {
+        compose.push_str("\nvolumes:\n  postgres-data:\n");
+    }
+    compose
+}
+
+/// Write `.devcontainer/` for `plan` into `project`
+///
+/// Returns the directory and the backups made of files setcyrup did not
+/// write, which are moved to `<file>.bak` rather than overwritten. A
+/// compose file setcyrup generated earlier is removed when the plan no
+/// longer has services; anything else in the directory is kept.
+pub fn write(plan: &InstallPlan, project: &Path) -> Result<(PathBuf, Vec<PathBuf>)> {
+    let dir = project.join(".devcontainer");
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let name = project_name(project);
+
+    let mut files = vec![
+        ("Dockerfile", dockerfile::render(plan)),
+        (
+            "devcontainer.json",
+            format!("// {}\n{}\n", MARKER, serde_json::to_string_pretty(&devcontainer_json(plan, &name))?),
+        ),
+    ];
+    let services = services(plan);
+    let compose_path = dir.join("docker-compose.yml");
+    if !services.is_empty() {
+        files.push(("docker-compose.yml", compose(&services, &name)));
+    } else if fs::read_to_string(&compose_path).is_ok_and(|contents| is_generated(&contents)) {
+        fs::remove_file(&compose_path).with_context(|| format!("Failed to remove {}", compose_path.display()))?;
+    }
+
+    let mut backups = Vec::new();
+    for (file, contents) in files {
+        let path = dir.join(file);
+        if fs::read_to_string(&path).is_ok_and(|existing| !is_generated(&existing)) {
+            let backup = dir.join(format!("{}.bak", file));
+            fs::rename(&path, &backup).with_context(|| format!("Failed to back up {}", path.display()))?;
+            backups.push(backup);
+        }
+        fs::write(&path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok((dir, backups))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::Accelerator;
+    use crate::menu::{InstallModule, InstallTarget};
+    use crate::package::Privilege;
+    use crate::steps::rust::RustSpec;
+
+    fn plan(components: Vec<(&str, Vec<Toolchain>, Vec<Service>)>) -> InstallPlan {
+        InstallPlan {
+            target: Some(InstallTarget::DevContainer),
+            components: components
+                .into_iter()
+                .map(|(name, toolchains, services)| InstallModule {
+                    name: name.to_string(),
+                    description: String::new(),
+                    dependencies: vec![],
+                    variants: vec![],
+                    toolchains,
+                    services,
+                    disk_mb: 0,
+                    commands: vec![],
+                })
+                .collect(),
+            accelerator: Accelerator::Cpu,
+            pytorch_index: None,
+            privilege: Privilege::Root,
+            rust: RustSpec::default(),
+        }
+    }
+
+    /// devcontainer.json minus its marker comment
+    fn read_config(dir: &Path) -> Value {
+        let contents = fs::read_to_string(dir.join("devcontainer.json")).unwrap();
+        let (marker, json) = contents.split_once('\n').unwrap();
+        assert!(is_generated(marker));
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_write_adds_compose_only_with_services() {
+        let project = tempfile::tempdir().unwrap();
+        let with_database = plan(vec![
+            ("Cyrup ML", vec![Toolchain::Python, Toolchain::Ml], vec![]),
+            ("Database", vec![], vec![Service::Postgres]),
+        ]);
+        let (dir, backups) = write(&with_database, project.path()).unwrap();
+        assert!(backups.is_empty());
+
+        let config = read_config(&dir);
+        assert_eq!(config["dockerComposeFile"], "docker-compose.yml");
+        assert_eq!(config["forwardPorts"], json!([8888, "db:5432"]));
+        assert!(config["customizations"]["vscode"]["extensions"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("ms-toolsai.jupyter")));
+        let compose = fs::read_to_string(dir.join("docker-compose.yml")).unwrap();
+        assert!(compose.contains("    image: postgres:16\n"));
+        assert!(dir.join("Dockerfile").exists());
+
+        // Dropping the database removes the generated compose file
+        let (_, backups) =
+            write(&plan(vec![("Cyrup ML", vec![Toolchain::Python, Toolchain::Ml], vec![])]), project.path()).unwrap();
+        assert!(backups.is_empty());
+        assert!(!dir.join("docker-compose.yml").exists());
+        assert_eq!(read_config(&dir)["build"]["dockerfile"], "Dockerfile");
+    }
+
+    #[test]
+    fn test_write_backs_up_handwritten_files() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path().join(".devcontainer");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Dockerfile"), "FROM debian\n").unwrap();
+
+        let (_, backups) = write(&plan(vec![("Go", vec![Toolchain::Go], vec![])]), project.path()).unwrap();
+        assert_eq!(backups, vec![dir.join("Dockerfile.bak")]);
+        assert_eq!(fs::read_to_string(dir.join("Dockerfile.bak")).unwrap(), "FROM debian\n");
+        assert!(is_generated(&fs::read_to_string(dir.join("Dockerfile")).unwrap()));
+    }
+}
@@ -0,0 +1,426 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::menu::{InstallModule, InstallPlan, Toolchain};
+use crate::package::catalog;
+use crate::package::manager::PackageManager;
+use crate::package::release::{Archive, ReleaseSpec};
+use crate::package::Package;
+use crate::setcyrup::{source_line, SHELL_CONFIGS};
+use crate::steps::cargo::{binstall_args, InstallMethod, BINSTALL_RELEASE_URL};
+use crate::steps::{go, ml, node, python, rust};
+
+/// Debian image with a non-root `vscode` user and passwordless sudo
+pub const BASE_IMAGE: &str = "mcr.microsoft.com/devcontainers/base:bookworm";
+
+/// User the toolchains are installed for
+pub const USER: &str = "vscode";
+const HOME: &str = "/home/vscode";
+
+/// Packages the toolchain installers themselves need
+const BASE_PACKAGES: &[&str] = &["ca-certificates", "curl", "git", "unzip", "xz-utils"];
+
+/// Quote `arg` for `sh` if it contains anything special
+fn quote(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "@%+=:,./_-".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+fn join_args<S: AsRef<str>>(args: &[S]) -> String {
+    args.iter().map(|arg| quote(arg.as_ref())).collect::<Vec<_>>().join(" ")
+}
+
+/// A `RUN` instruction chaining `commands`, one per line
+fn run(commands: &[String]) -> String {
+    format!("RUN {}\n", commands.join(" \\\n && "))
+}
+
+/// Toolchains `component` needs in install order
+///
+/// The ML environment is built with uv, so Python always comes first.
+fn component_toolchains(component: &InstallModule) -> Vec<Toolchain> {
+    let mut toolchains = Vec::new();
+    for toolchain in &component.toolchains {
+        if *toolchain == Toolchain::Ml && !toolchains.contains(&Toolchain::Python) {
+            toolchains.push(Toolchain::Python);
+        }
+        if !toolchains.contains(toolchain) {
+            toolchains.push(toolchain.clone());
+        }
+    }
+    toolchains
+}
+
+/// apt packages for a component and the toolchains it brings in, sorted so
+/// the output is stable
+fn component_packages(component: &InstallModule, plan: &InstallPlan, toolchains: &[Toolchain]) -> BTreeSet<String> {
+    let mut packages = BTreeSet::new();
+    let mut catalog_groups = Vec::new();
+    if toolchains.contains(&Toolchain::Rust) {
+        catalog_groups.push("build-essential");
+    }
+    if toolchains.contains(&Toolchain::Python) {
+        catalog_groups.push("python-base");
+    }
+    for package in catalog::get_many(&catalog_groups) {
+        packages.extend(package.names_for(&PackageManager::Apt).into_iter().map(String::from));
+    }
+
+    for dependency in component.dependencies_for(plan.accelerator) {
+        let package = Package::new(dependency);
+        packages.extend(package.names_for(&PackageManager::Apt).into_iter().map(String::from));
+    }
+    packages
+}
+
+fn apt_layer(packages: &BTreeSet<String>) -> String {
+    let list: String = packages.iter().map(|package| format!("    {} \\\n", package)).collect();
+    format!(
+        "RUN apt-get update \\\n \
+         && DEBIAN_FRONTEND=noninteractive apt-get install -y --no-install-recommends \\\n\
+         {} && rm -rf /var/lib/apt/lists/*\n",
+        list
+    )
+}
+
+fn rust_layer(plan: &InstallPlan) -> String {
+    let mut setup = vec![format!(
+        "curl --proto '=https' --tlsv1.2 -sSf {} | sh -s -- -y --no-modify-path --profile minimal --default-toolchain none",
+        rust::RUSTUP_INSTALL_URL
+    )];
+    setup.extend(plan.rust.setup_commands().iter().map(|args| format!("rustup {}", join_args(args))));
+
+    let mut crates = vec![format!(
+        "curl --proto '=https' --tlsv1.2 -fsSL {}/cargo-binstall-$(uname -m)-unknown-linux-musl.tgz | tar -xz -C {}/.cargo/bin",
+        BINSTALL_RELEASE_URL, HOME
+    )];
+    for spec in catalog::get_cargo_packages() {
+        let source = format!("cargo {}", join_args(&spec.install_args()));
+        crates.push(match spec.binstall_name() {
+            Some(name) => format!(
+                "(cargo binstall {} || {})",
+                join_args(&binstall_args(&name, InstallMethod::Release, None)),
+                source
+            ),
+            None => source,
+        });
+    }
+
+    format!("{}{}", run(&setup), run(&crates))
+}
+
+fn python_layer() -> String {
+    let mut commands = vec![
+        format!(
+            "curl --proto '=https' --tlsv1.2 -LsSf {} | sh -s -- --no-modify-path",
+            python::UV_INSTALL_URL
+        ),
+        format!("uv python install {}", python::PYTHON_VERSIONS.join(" ")),
+    ];
+    for (package, with) in python::TOOLS {
+        let with: String = with.iter().map(|extra| format!("--with {} ", quote(extra))).collect();
+        commands.push(format!("uv tool install {}{}", with, quote(package)));
+    }
+    run(&commands)
+}
+
+fn ml_layer(plan: &InstallPlan) -> String {
+    let project = format!("{}/.local/share/cyrup/ml", HOME);
+    format!(
+        // COPY would create missing parent directories owned by root
+        "RUN mkdir -p {project}\nCOPY --chown={user}:{user} <<'EOF' {project}/pyproject.toml\n{pyproject}EOF\n{run}",
+        user = USER,
+        project = project,
+        pyproject = ml::pyproject(plan.pytorch_index.as_deref()),
+        run = run(&[
+            format!("cd {}", project),
+            "uv lock".to_string(),
+            format!("uv sync --locked --python {}", python::PYTHON_VERSIONS[0]),
+            format!(
+                ".venv/bin/python -m ipykernel install --user --name {} --display-name {}",
+                ml::KERNEL_NAME,
+                quote(&ml::display_name(plan.accelerator))
+            ),
+        ])
+    )
+}
+
+fn node_layer() -> String {
+    let using = format!("--using={}", node::NODE_VERSION);
+    run(&[
+        format!(
+            "curl --proto '=https' --tlsv1.2 -fsSL {} | bash -s -- --install-dir {}/.local/share/fnm --skip-shell",
+            node::FNM_INSTALL_URL,
+            HOME
+        ),
+        format!("fnm install {}", node::NODE_VERSION),
+        format!("fnm default {}", node::NODE_VERSION),
+        format!("fnm exec {} corepack enable", using),
+    ])
+}
+
+fn go_layer() -> String {
+    // BuildKit's TARGETARCH uses the same amd64/arm64 names as Go
+    let url = format!("{}/go{}.linux-${{TARGETARCH}}.tar.gz", go::DOWNLOAD_URL, go::GO_VERSION);
+    let mut commands = vec![
+        format!("curl --proto '=https' --tlsv1.2 -fsSLo /tmp/go.tar.gz {}", url),
+        format!(
+            "echo \"$(curl --proto '=https' --tlsv1.2 -fsSL {}.sha256)  /tmp/go.tar.gz\" | sha256sum -c -",
+            url
+        ),
+        format!("mkdir -p {}/.local/share", HOME),
+        format!("tar -C {}/.local/share -xzf /tmp/go.tar.gz", HOME),
+        "rm /tmp/go.tar.gz".to_string(),
+    ];
+    // The catalog list, not CYRUP_GO_TOOLS, so the image does not depend on
+    // the environment of whoever generated it
+    for package in catalog::get_go_tools() {
+        commands.push(format!("go install {}", quote(package)));
+    }
+    run(&commands)
+}
+
+/// Install one release binary, verified like `ReleaseInstaller` does
+fn release_layer(spec: &ReleaseSpec) -> String {
+    let arches: String = ["amd64", "arm64"]
+        .iter()
+        .map(|arch| {
+            format!(
+                "      {}) url={}; sums={} ;; \\\n",
+                arch,
+                quote(&spec.asset_url("linux", arch)),
+                quote(&spec.checksum_url("linux", arch))
+            )
+        })
+        .collect();
+    let binary = spec.binary_name();
+    let unpack = format!("/tmp/{}", spec.name);
+    let install = match Archive::from_asset(&spec.asset_url("linux", "amd64")) {
+        Archive::Tar => vec![
+            format!("mkdir -p {}", unpack),
+            format!("tar -xf \"/tmp/$asset\" -C {}", unpack),
+            format!(
+                "install -m 755 \"$(find {} -type f -name {} | head -n 1)\" {}/.local/bin/{}",
+                unpack, binary, HOME, binary
+            ),
+        ],
+        Archive::Zip => vec![
+            format!("unzip -q -o \"/tmp/$asset\" -d {}", unpack),
+            format!(
+                "install -m 755 \"$(find {} -type f -name {} | head -n 1)\" {}/.local/bin/{}",
+                unpack, binary, HOME, binary
+            ),
+        ],
+        Archive::Binary => vec![format!("install -m 755 \"/tmp/$asset\" {}/.local/bin/{}", HOME, binary)],
+    };
+
+    let mut commands = vec![
+        format!(
+            "case \"$TARGETARCH\" in \\\n{}      *) echo \"No {} release for $TARGETARCH\" >&2; exit 1 ;; \\\n    esac",
+            arches, spec.name
+        ),
+        "asset=\"${url##*/}\"".to_string(),
+        "curl --proto '=https' --tlsv1.2 -fsSLo \"/tmp/$asset\" \"$url\"".to_string(),
+        // Sidecar files hold just the digest, lists one `<digest>  <asset>` per line
+        "echo \"$(curl --proto '=https' --tlsv1.2 -fsSL \"$sums\" \
+         | awk -v asset=\"$asset\" 'NF == 1 || $NF == asset || $NF == \"*\" asset { print $1; exit }')  /tmp/$asset\" \
+         | sha256sum -c -"
+            .to_string(),
+        format!("mkdir -p {}/.local/bin", HOME),
+    ];
+    commands.extend(install);
+    commands.push(format!("rm -rf \"/tmp/$asset\" {}", unpack));
+    run(&commands)
+}
+
+/// Copy the Cyrup shell configs and source them from the user's rc files
+fn shell_layer() -> String {
+    let shell_dir = format!("{}/.config/cyrup/shell", HOME);
+    let mut out = format!("RUN mkdir -p {}\n", shell_dir);
+    let mut commands = Vec::new();
+    for (rc, config, contents) in SHELL_CONFIGS {
+        out.push_str(&format!(
+            "COPY --chown={user}:{user} <<'EOF' {dir}/{config}\n{contents}EOF\n",
+            user = USER,
+            dir = shell_dir,
+            config = config,
+            contents = contents
+        ));
+        let line = source_line(Path::new(&shell_dir), config).replace('\n', "\\n");
+        commands.push(format!("printf '{}' >> {}/{}", line, HOME, rc));
+    }
+    out.push_str(&run(&commands));
+    out
+}
+
+/// Render a Dockerfile that sets up `plan` for the `vscode` user
+///
+/// Each component gets its own layers: its packages are installed as root
+/// in one `RUN`, then the toolchains it brings in are installed as the
+/// user like setcyrup does on a host. The output only depends on the plan,
+/// so it can be compared against a snapshot.
+pub fn render(plan: &InstallPlan) -> String {
+    let names: Vec<&str> = plan.components.iter().map(|c| c.name.as_str()).collect();
+    let mut out = format!(
+        "# syntax=docker/dockerfile:1\n\
+         # Generated by setcyrup for: {}\n\
+         FROM {}\n\
+         ARG TARGETARCH\n\n\
+         # Base\n\
+         USER root\n",
+        names.join(", "),
+        BASE_IMAGE
+    );
+    let mut installed: BTreeSet<String> = BASE_PACKAGES.iter().map(|p| p.to_string()).collect();
+    out.push_str(&apt_layer(&installed));
+    out.push_str(&format!("USER {}\nWORKDIR {}\n", USER, HOME));
+
+    let mut path_dirs: Vec<String> = Vec::new();
+    let mut add_path = |out: &mut String, dir: String| {
+        if !path_dirs.contains(&dir) {
+            out.push_str(&format!("ENV PATH={}:$PATH\n", dir));
+            path_dirs.push(dir);
+        }
+    };
+
+    let mut emitted: Vec<Toolchain> = Vec::new();
+    for component in &plan.components {
+        out.push_str(&format!("\n# Component: {}\n", component.name));
+        let toolchains: Vec<Toolchain> = component_toolchains(component)
+            .into_iter()
+            .filter(|toolchain| !emitted.contains(toolchain))
+            .collect();
+
+        let packages: BTreeSet<String> = component_packages(component, plan, &toolchains)
+            .difference(&installed)
+            .cloned()
+            .collect();
+        if !packages.is_empty() {
+            out.push_str("USER root\n");
+            out.push_str(&apt_layer(&packages));
+            out.push_str(&format!("USER {}\n", USER));
+            installed.extend(packages);
+        }
+
+        for toolchain in &toolchains {
+            match toolchain {
+                Toolchain::Rust => {
+                    out.push_str("# Rust\n");
+                    add_path(&mut out, format!("{}/.cargo/bin", HOME));
+                    out.push_str(&rust_layer(plan));
+                }
+                Toolchain::Python => {
+                    out.push_str("# Python\n");
+                    add_path(&mut out, format!("{}/.local/bin", HOME));
+                    out.push_str(&python_layer());
+                }
+                Toolchain::Ml => {
+                    out.push_str("# ML environment\n");
+                    out.push_str(&ml_layer(plan));
+                }
+                Toolchain::Node => {
+                    out.push_str("# Node.js\n");
+                    add_path(&mut out, format!("{0}/.local/share/fnm:{0}/.local/share/fnm/aliases/default/bin", HOME));
+                    out.push_str(&node_layer());
+                }
+                Toolchain::Go => {
+                    out.push_str("# Go\n");
+                    out.push_str(&format!("ENV GOROOT={0}/.local/share/go GOPATH={0}/go\n", HOME));
+                    add_path(&mut out, "$GOROOT/bin:$GOPATH/bin".to_string());
+                    out.push_str(&go_layer());
+                }
+                Toolchain::Releases => {
+                    out.push_str("# Release binaries\n");
+                    add_path(&mut out, format!("{}/.local/bin", HOME));
+                    for spec in catalog::get_release_binaries() {
+                        out.push_str(&release_layer(&spec));
+                    }
+                }
+            }
+        }
+        emitted.extend(toolchains);
+    }
+
+    out.push_str("\n# Shell configuration\n");
+    out.push_str(&shell_layer());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkpoint::SavedPlan;
+    use crate::gpu::Accelerator;
+    use crate::menu::{InstallModule, InstallTarget, Service};
+    use crate::package::Privilege;
+    use crate::steps::rust::RustSpec;
+
+    fn module(name: &str, dependencies: &[&str], toolchains: Vec<Toolchain>, services: Vec<Service>) -> InstallModule {
+        InstallModule {
+            name: name.to_string(),
+            description: String::new(),
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            variants: vec![],
+            toolchains,
+            services,
+            disk_mb: 0,
+            commands: vec![],
+        }
+    }
+
+    #[test]
+    fn test_render_layers_components_and_orders_toolchains() {
+        let plan = InstallPlan {
+            target: Some(InstallTarget::DevContainer),
+            components: vec![
+                module("Cyrup ML", &["python3-pip"], vec![Toolchain::Ml], vec![]),
+                module("Database", &["postgresql-client", "libpq-dev"], vec![], vec![Service::Postgres]),
+            ],
+            accelerator: Accelerator::Cpu,
+            pytorch_index: None,
+            privilege: Privilege::Root,
+            rust: RustSpec::default(),
+        };
+        let dockerfile = render(&plan);
+        assert!(dockerfile.contains("    libssl-dev \\\n    python3-pip \\\n"));
+        // Each component installs its own packages, as root
+        assert!(dockerfile.contains(
+            "# Component: Database\nUSER root\nRUN apt-get update \\\n \
+             && DEBIAN_FRONTEND=noninteractive apt-get install -y --no-install-recommends \\\n    \
+             libpq-dev \\\n    postgresql-client \\\n && rm -rf /var/lib/apt/lists/*\nUSER vscode\n"
+        ));
+        assert!(dockerfile.contains(" && rm -rf /var/lib/apt/lists/*\n"));
+        assert!(dockerfile.find("uv python install").unwrap() < dockerfile.find("uv sync --locked").unwrap());
+        assert!(dockerfile.contains("--display-name 'Cyrup ML (CPU only)'"));
+        assert_eq!(dockerfile.matches("ENV PATH=").count(), 1);
+    }
+
+    #[test]
+    fn test_render_matches_snapshot() {
+        let saved = SavedPlan {
+            target: Some("host".to_string()),
+            components: vec![
+                "Cyrup ML".to_string(),
+                "Cyrup Developer".to_string(),
+                "Cyrup Database (PostgreSQL)".to_string(),
+            ],
+            accelerator: Some("cuda".to_string()),
+            pytorch_index: Some("https://download.pytorch.org/whl/cu124".to_string()),
+            rust: RustSpec::default(),
+        };
+        let plan = InstallPlan::from_saved(&saved, Privilege::Root).unwrap();
+        assert_eq!(render(&plan), include_str!("fixtures/export.Dockerfile"));
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("dioxus-cli@0.6"), "dioxus-cli@0.6");
+        assert_eq!(quote("quick-install,compile"), "quick-install,compile");
+        assert_eq!(quote("{ name }"), "'{ name }'");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+    }
+}
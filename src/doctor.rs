@@ -0,0 +1,441 @@
+use anyhow::{anyhow, Context, Result};
+use dirs::home_dir;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use which::which;
+
+use crate::environment::Environment;
+use crate::gpu::{self, CudaStack, GpuType};
+use crate::package::sys::PackageCache;
+use crate::preflight::{self, Check};
+use crate::steps::{node, path::PathEntry, python, rust, Step, StepNotes};
+
+/// Shell rc files inspected for duplicate entries
+const RC_FILES: &[&str] = &[".bashrc", ".zshrc", ".profile"];
+
+/// A safe, automatic repair for a problem
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fix {
+    /// Drop repeated Cyrup blocks and adjacent repeated lines
+    DedupeRcFile(PathBuf),
+    /// Delete package cache entries past their freshness window
+    ClearCache(Vec<PathBuf>),
+    /// Add a directory to PATH in the shell rc files
+    AddToPath(PathBuf),
+}
+
+impl Fix {
+    fn apply(&self) -> Result<()> {
+        match self {
+            Fix::DedupeRcFile(path) => {
+                let contents = fs::read_to_string(path)?;
+                let backup = path.with_extension("setcyrup-backup");
+                fs::write(&backup, &contents)
+                    .with_context(|| format!("Failed to back up {}", path.display()))?;
+                fs::write(path, dedupe_lines(&contents))
+                    .with_context(|| format!("Failed to write {}", path.display()))
+            }
+            Fix::ClearCache(entries) => {
+                for entry in entries {
+                    fs::remove_file(entry)
+                        .with_context(|| format!("Failed to remove {}", entry.display()))?;
+                }
+                Ok(())
+            }
+            Fix::AddToPath(dir) => {
+                PathEntry::new("doctor.path", "Doctor", dir.clone()).apply(&mut StepNotes::default())
+            }
+        }
+    }
+}
+
+/// A check result and, when it failed, how to repair it automatically
+struct Diagnosis {
+    check: Check,
+    fix: Option<Fix>,
+}
+
+impl From<Check> for Diagnosis {
+    fn from(check: Check) -> Self {
+        Self { check, fix: None }
+    }
+}
+
+/// Inspect the environment, optionally repairing what is safe to repair
+pub fn run(fix: bool) -> Result<()> {
+    let mut diagnoses = vec![check_cargo_path()];
+    diagnoses.extend(check_rc_files()?);
+    diagnoses.push(check_rustup().into());
+    diagnoses.push(check_python_tools().into());
+    diagnoses.push(check_node().into());
+    diagnoses.push(check_package_cache()?);
+    diagnoses.extend(check_gpu()?.into_iter().map(Diagnosis::from));
+
+    let checks: Vec<Check> = diagnoses.iter().map(|d| d.check.clone()).collect();
+    preflight::print("setcyrup doctor", &checks);
+
+    let fixable: Vec<&Fix> = diagnoses
+        .iter()
+        .filter(|d| d.check.is_failed())
+        .filter_map(|d| d.fix.as_ref())
+        .collect();
+    let mut remaining = checks.iter().filter(|check| check.is_failed()).count();
+
+    if fix {
+        for repair in &fixable {
+            repair.apply()?;
+            println!("🔧 {}", describe(repair));
+            remaining -= 1;
+        }
+    } else if !fixable.is_empty() {
+        println!("{} problem(s) can be fixed automatically with `setcyrup doctor --fix`", fixable.len());
+    }
+
+    if remaining > 0 {
+        return Err(anyhow!("{} problem(s) need attention", remaining));
+    }
+    Ok(())
+}
+
+fn describe(fix: &Fix) -> String {
+    match fix {
+        Fix::DedupeRcFile(path) => format!(
+            "Removed duplicate lines from {} (backup in {})",
+            path.display(),
+            path.with_extension("setcyrup-backup").display()
+        ),
+        Fix::ClearCache(entries) => format!("Removed {} stale cache entries", entries.len()),
+        Fix::AddToPath(dir) => format!("Added {} to PATH; restart your terminal", dir.display()),
+    }
+}
+
+/// Directories cargo may live in, in the order setcyrup prefers them
+///
+/// `CARGO_HOME` is usually one of the defaults, e.g. shell.rs exports
+/// `$XDG_DATA_HOME/cargo`, so each directory is listed once.
+fn cargo_dirs(cargo_home: Option<PathBuf>, home: Option<&Path>) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(cargo_home) = cargo_home {
+        candidates.push(cargo_home.join("bin"));
+    }
+    if let Some(home) = home {
+        // ~/.cargo is rustup's default, shell.rs uses $XDG_DATA_HOME/cargo
+        candidates.push(home.join(".cargo/bin"));
+        candidates.push(home.join(".local/share/cargo/bin"));
+    }
+
+    let mut dirs = Vec::new();
+    for dir in candidates {
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+fn check_cargo_path() -> Diagnosis {
+    let path = env::var("PATH").unwrap_or_default();
+    let installed: Vec<PathBuf> = cargo_dirs(env::var_os("CARGO_HOME").map(PathBuf::from), home_dir().as_deref())
+        .into_iter()
+        .filter(|dir| dir.join("cargo").exists())
+        .collect();
+    cargo_path_diagnosis(&path, &installed)
+}
+
+/// Compare PATH against the cargo installations that exist on disk
+fn cargo_path_diagnosis(path: &str, installed: &[PathBuf]) -> Diagnosis {
+    const NAME: &str = "Cargo on PATH";
+    let position = |dir: &Path| env::split_paths(path).position(|entry| entry == dir);
+
+    match installed {
+        [] => Check::passed(NAME, "cargo is not installed").into(),
+        [dir] => match position(dir) {
+            Some(_) => Check::passed(NAME, dir.display().to_string()).into(),
+            None => Diagnosis {
+                check: Check::failed(
+                    NAME,
+                    format!("{} is not on PATH", dir.display()),
+                    "Add it to your shell rc file",
+                ),
+                fix: Some(Fix::AddToPath(dir.clone())),
+            },
+        },
+        dirs => {
+            let mut on_path: Vec<&PathBuf> = dirs.iter().filter(|dir| position(dir).is_some()).collect();
+            on_path.sort_by_key(|dir| position(dir));
+            let winner = on_path
+                .first()
+                .map_or_else(|| "neither is on PATH".to_string(), |dir| format!("{} wins", dir.display()));
+            let listed: Vec<String> = dirs.iter().map(|dir| dir.display().to_string()).collect();
+            Check::failed(
+                NAME,
+                format!("cargo is installed in {}; {}", listed.join(" and "), winner),
+                "Keep one installation: set CARGO_HOME consistently in your shell config \
+                 and remove the other directory",
+            )
+            .into()
+        }
+    }
+}
+
+fn check_rc_files() -> Result<Vec<Diagnosis>> {
+    let home = home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+    let mut diagnoses = Vec::new();
+
+    for rc in RC_FILES {
+        let path = home.join(rc);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(_) => continue,
+        };
+
+        let deduped = dedupe_lines(&contents);
+        let removed = contents.lines().count() - deduped.lines().count();
+        let repeated = repeated_entries(&deduped);
+        diagnoses.push(if removed > 0 {
+            Diagnosis {
+                check: Check::failed(
+                    "Shell rc files",
+                    format!("~/{} repeats {} line(s) setcyrup can remove", rc, removed),
+                    "Remove the repeated Cyrup blocks",
+                ),
+                fix: Some(Fix::DedupeRcFile(path)),
+            }
+        } else if repeated > 0 {
+            // Order matters outside Cyrup blocks, e.g. PATH set again after a reset
+            Check::failed(
+                "Shell rc files",
+                format!("~/{} repeats {} export or source line(s)", rc, repeated),
+                "Check whether the repeated lines are intended and remove the ones that are not",
+            )
+            .into()
+        } else {
+            Check::passed("Shell rc files", format!("~/{} has no duplicate entries", rc)).into()
+        });
+    }
+
+    Ok(diagnoses)
+}
+
+fn is_entry(line: &str) -> bool {
+    ["export ", "source ", ". ", "eval "]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+}
+
+/// Whether `line` is the `source` line setcyrup writes after `# Cyrup`
+fn is_cyrup_source(line: &str) -> bool {
+    line.starts_with("source ") && line.contains("/.config/cyrup/shell/")
+}
+
+/// Remove what setcyrup can safely drop, keeping the first occurrence
+///
+/// That is repeated Cyrup blocks, the `# Cyrup` marker and its `source`
+/// line, and an export, source or eval line repeated right after itself.
+/// Other repeats are left alone since their order may matter.
+fn dedupe_lines(contents: &str) -> String {
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut seen_blocks = std::collections::HashSet::new();
+    let mut output: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let trimmed = lines[i].trim();
+        if trimmed == "# Cyrup" {
+            if let Some(next) = lines.get(i + 1).map(|line| line.trim()) {
+                if is_cyrup_source(next) && !seen_blocks.insert(next) {
+                    // setcyrup puts a blank line before each block
+                    if output.last().is_some_and(|line| line.trim().is_empty()) {
+                        output.pop();
+                    }
+                    i += 2;
+                    continue;
+                }
+            }
+        }
+        let repeats_previous = output.last().is_some_and(|line| line.trim() == trimmed);
+        if !(is_entry(trimmed) && repeats_previous) {
+            output.push(lines[i]);
+        }
+        i += 1;
+    }
+
+    output.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// Number of export, source or eval lines that repeat an earlier one
+fn repeated_entries(contents: &str) -> usize {
+    let mut seen = std::collections::HashSet::new();
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| is_entry(line) && !seen.insert(*line))
+        .count()
+}
+
+fn check_rustup() -> Check {
+    const NAME: &str = "Rust toolchain";
+    let rustup = rust::rustup_bin("rustup");
+    if which(&rustup).is_err() {
+        return Check::passed(NAME, "rustup is not installed");
+    }
+
+    match Command::new(&rustup).args(["show", "active-toolchain"]).output() {
+        Ok(output) if output.status.success() => {
+            let toolchain = String::from_utf8_lossy(&output.stdout);
+            Check::passed(NAME, toolchain.split_whitespace().next().unwrap_or("unknown").to_string())
+        }
+        Ok(output) => Check::failed(
+            NAME,
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            "Run `rustup default stable` to install and select a toolchain",
+        ),
+        Err(e) => Check::failed(NAME, e.to_string(), "Reinstall rustup from https://rustup.rs"),
+    }
+}
+
+fn check_python_tools() -> Check {
+    const NAME: &str = "Python tools";
+    let bin_dir = python::uv_bin_dir().ok();
+    let found = |tool: &str| {
+        which(tool).is_ok() || bin_dir.as_ref().is_some_and(|dir| dir.join(tool).exists())
+    };
+    let rye = home_dir().is_some_and(|home| home.join(".rye").exists());
+
+    match (found("uv"), rye) {
+        (_, true) => Check::failed(
+            NAME,
+            "Rye is still installed; it is deprecated and setcyrup now uses uv",
+            "Run `rye self uninstall`, then `setcyrup` to set up uv",
+        ),
+        (true, false) => Check::passed(NAME, "uv is installed"),
+        (false, false) => Check::passed(NAME, "not installed"),
+    }
+}
+
+fn check_node() -> Check {
+    const NAME: &str = "Node.js";
+    if !node::fnm_dir().is_ok_and(|dir| dir.join("fnm").exists()) {
+        return Check::passed(NAME, "fnm is not installed");
+    }
+
+    let versions = node::installed_versions();
+    let default = versions
+        .iter()
+        .find(|(_, aliases)| aliases.iter().any(|alias| alias == "default"))
+        .map(|(version, _)| version.as_str());
+    match default {
+        Some(version) if version == node::NODE_VERSION => Check::passed(NAME, format!("{} through fnm", version)),
+        Some(version) => Check::failed(
+            NAME,
+            format!("fnm defaults to {}, setcyrup pins {}", version, node::NODE_VERSION),
+            format!("Run `fnm default {}` or rerun setcyrup", node::NODE_VERSION),
+        ),
+        None => Check::failed(
+            NAME,
+            "fnm has no default Node.js version",
+            format!("Run `fnm install {0} && fnm default {0}`", node::NODE_VERSION),
+        ),
+    }
+}
+
+fn check_package_cache() -> Result<Diagnosis> {
+    const NAME: &str = "Package cache";
+    let stale = PackageCache::new()?.stale_entries()?;
+    Ok(if stale.is_empty() {
+        Check::passed(NAME, "fresh").into()
+    } else {
+        Diagnosis {
+            check: Check::failed(
+                NAME,
+                format!("{} entries in ~/.cache/cypackages are stale", stale.len()),
+                "Delete them so the next run refreshes package metadata",
+            ),
+            fix: Some(Fix::ClearCache(stale)),
+        }
+    })
+}
+
+fn check_gpu() -> Result<Vec<Check>> {
+    const NAME: &str = "GPU";
+    let devices = gpu::detect_gpu()?;
+    let environment = Environment::detect();
+    let mut checks = Vec::new();
+
+    for device in &devices {
+        if let GpuType::DriverMissing(pci) = device {
+            checks.push(Check::failed(
+                NAME,
+                format!("{} has no driver", pci.name()),
+                gpu::driver_hint(pci.vendor(), &environment),
+            ));
+        }
+    }
+
+    if devices.iter().any(|device| matches!(device, GpuType::Nvidia(_))) {
+        for issue in CudaStack::detect(Path::new("/"))?.check() {
+            checks.push(Check::failed(NAME, issue.to_string(), "See the CUDA compatibility notes above"));
+        }
+    }
+
+    if checks.is_empty() {
+        let summary = match devices.first() {
+            Some(device) => gpu::describe(device),
+            None => "none detected".to_string(),
+        };
+        checks.push(Check::passed(NAME, summary));
+    }
+    Ok(checks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedupe_lines_only_touches_cyrup_blocks() {
+        let rc = "export PATH=\"$HOME/.cargo/bin:$PATH\"\nif [ -f x ]; then\nfi\n\n# Cyrup\nsource ~/.config/cyrup/shell/bashrc\n\
+                  if [ -f y ]; then\nfi\n\n# Cyrup\nsource ~/.config/cyrup/shell/bashrc\n\
+                  eval \"$(fnm env)\"\neval \"$(fnm env)\"\nexport PATH=\"$HOME/.cargo/bin:$PATH\"\n";
+        let deduped = dedupe_lines(rc);
+        assert_eq!(
+            deduped,
+            "export PATH=\"$HOME/.cargo/bin:$PATH\"\nif [ -f x ]; then\nfi\n\n# Cyrup\nsource ~/.config/cyrup/shell/bashrc\n\
+             if [ -f y ]; then\nfi\neval \"$(fnm env)\"\nexport PATH=\"$HOME/.cargo/bin:$PATH\"\n"
+        );
+        // The second PATH export may be intended, so it is only reported
+        assert_eq!(repeated_entries(&deduped), 1);
+    }
+
+    #[test]
+    fn test_cargo_path_diagnosis() {
+        let home_cargo = PathBuf::from("/home/dev/.cargo/bin");
+        let xdg_cargo = PathBuf::from("/home/dev/.local/share/cargo/bin");
+
+        let ok = cargo_path_diagnosis("/home/dev/.cargo/bin:/usr/bin", std::slice::from_ref(&home_cargo));
+        assert!(!ok.check.is_failed());
+
+        let missing = cargo_path_diagnosis("/usr/bin", std::slice::from_ref(&home_cargo));
+        assert_eq!(missing.fix, Some(Fix::AddToPath(home_cargo.clone())));
+
+        // CARGO_HOME from shell.rs is the XDG default, listed once
+        let dirs = cargo_dirs(Some(PathBuf::from("/home/dev/.local/share/cargo")), Some(Path::new("/home/dev")));
+        assert_eq!(dirs, vec![xdg_cargo.clone(), home_cargo.clone()]);
+        let installed: Vec<PathBuf> = dirs.into_iter().filter(|dir| *dir == xdg_cargo).collect();
+        let xdg_only = cargo_path_diagnosis("/home/dev/.local/share/cargo/bin:/usr/bin", &installed);
+        assert!(!xdg_only.check.is_failed());
+
+        let conflict = cargo_path_diagnosis(
+            "/home/dev/.local/share/cargo/bin:/home/dev/.cargo/bin:/usr/bin",
+            &[home_cargo, xdg_cargo],
+        );
+        assert!(conflict.check.is_failed());
+        assert_eq!(conflict.fix, None);
+        assert!(matches!(
+            conflict.check.status,
+            preflight::Status::Failed { ref problem, .. }
+                if problem.ends_with("/home/dev/.local/share/cargo/bin wins")
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+use serde::Serialize;
+use std::env;
+use std::fs;
+use std::path::Path;
+use which::which;
+
+use crate::menu::{InstallPlan, InstallTarget};
+
+/// CI providers and the variable each one sets
+const CI_VARS: &[(&str, &str)] = &[
+    ("GITHUB_ACTIONS", "github-actions"),
+    ("GITLAB_CI", "gitlab-ci"),
+    ("BUILDKITE", "buildkite"),
+    ("CIRCLECI", "circleci"),
+    ("JENKINS_URL", "jenkins"),
+    ("TF_BUILD", "azure-pipelines"),
+];
+
+/// What kind of machine setcyrup is running on
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Virtualization {
+    BareMetal,
+    /// Docker, Podman, Kubernetes or LXC
+    Container { runtime: String },
+    Vm { hypervisor: Option<String> },
+    Wsl,
+}
+
+/// Where setcyrup runs, and what that implies for the install
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Environment {
+    pub virtualization: Virtualization,
+    /// Inside a VS Code dev container or GitHub Codespace
+    pub devcontainer: bool,
+    /// CI provider, e.g. "github-actions"
+    pub ci: Option<String>,
+    pub is_root: bool,
+    /// Whether systemd is running, so services can be registered
+    pub has_systemd: bool,
+}
+
+impl Environment {
+    /// Probe the machine setcyrup is running on
+    pub fn detect() -> Self {
+        // SAFETY: geteuid has no preconditions
+        let is_root = unsafe { libc::geteuid() } == 0;
+        Self::probe(Path::new("/"), |name| env::var(name).ok(), is_root)
+    }
+
+    /// Probe below `root`, reading variables through `var`
+    fn probe(root: &Path, var: impl Fn(&str) -> Option<String>, is_root: bool) -> Self {
+        let set = |name: &str| var(name).is_some_and(|value| !value.is_empty() && value != "false");
+
+        let ci = CI_VARS
+            .iter()
+            .find(|(name, _)| set(name))
+            .map(|(_, provider)| provider.to_string())
+            .or_else(|| set("CI").then(|| "unknown".to_string()));
+
+        Self {
+            virtualization: detect_virtualization(root),
+            devcontainer: set("REMOTE_CONTAINERS") || set("CODESPACES") || set("DEVCONTAINER"),
+            ci,
+            is_root,
+            has_systemd: root.join("run/systemd/system").is_dir(),
+        }
+    }
+
+    pub fn in_container(&self) -> bool {
+        self.devcontainer || matches!(self.virtualization, Virtualization::Container { .. })
+    }
+
+    /// Install targets that make sense here, the preferred one first
+    ///
+    /// Inside a container or on CI there is no Docker to build a dev
+    /// container with, so only the host is offered. A project that
+    /// already has a `.devcontainer` suggests the user wants one.
+    pub fn targets(&self, project: &Path) -> Vec<InstallTarget> {
+        if self.in_container() || self.ci.is_some() {
+            vec![InstallTarget::Host]
+        } else if project.join(".devcontainer").is_dir() && which("docker").is_ok() {
+            vec![InstallTarget::DevContainer, InstallTarget::Host]
+        } else {
+            vec![InstallTarget::Host, InstallTarget::DevContainer]
+        }
+    }
+
+    /// How the environment changes the plan, for the confirmation screen
+    pub fn notes(&self, plan: &InstallPlan) -> Vec<String> {
+        let mut notes = Vec::new();
+
+        if self.is_root {
+            notes.push("Running as root: packages are installed without sudo".to_string());
+        }
+        if self.in_container() {
+            notes.push(
+                "Running inside a container: GPUs are only visible when passed through, \
+                 e.g. `docker run --gpus all`"
+                    .to_string(),
+            );
+        }
+        if matches!(self.virtualization, Virtualization::Wsl) {
+            notes.push("Running under WSL: use the Windows GPU driver, not a Linux one".to_string());
+        }
+        if !self.has_systemd && plan.components.iter().any(|c| c.name.contains("cyrupd")) {
+            notes.push("systemd is not running: start cyrupd manually instead of as a service".to_string());
+        }
+        if let Some(ci) = &self.ci {
+            notes.push(format!("Running on CI ({}): shell rc changes only affect later jobs", ci));
+        }
+
+        notes
+    }
+}
+
+/// Tell containers, WSL and virtual machines apart below `root`
+pub fn detect_virtualization(root: &Path) -> Virtualization {
+    let read = |path: &str| fs::read_to_string(root.join(path)).unwrap_or_default();
+
+    if root.join(".dockerenv").exists() {
+        return Virtualization::Container { runtime: "docker".to_string() };
+    }
+    if root.join("run/.containerenv").exists() {
+        return Virtualization::Container { runtime: "podman".to_string() };
+    }
+    let cgroup = read("proc/1/cgroup");
+    for runtime in ["kubepods", "docker", "containerd", "lxc"] {
+        if cgroup.contains(runtime) {
+            return Virtualization::Container { runtime: runtime.to_string() };
+        }
+    }
+
+    let version = read("proc/version").to_ascii_lowercase();
+    if version.contains("microsoft") || version.contains("wsl") {
+        return Virtualization::Wsl;
+    }
+
+    let vendor = read("sys/class/dmi/id/sys_vendor").trim().to_string();
+    let hypervisor = match vendor.as_str() {
+        "QEMU" => Some("kvm"),
+        "VMware, Inc." => Some("vmware"),
+        "innotek GmbH" => Some("virtualbox"),
+        "Microsoft Corporation" => Some("hyper-v"),
+        "Amazon EC2" => Some("ec2"),
+        "Google" => Some("gce"),
+        "Xen" => Some("xen"),
+        _ => None,
+    };
+    if hypervisor.is_some() || read("proc/cpuinfo").contains(" hypervisor") {
+        return Virtualization::Vm { hypervisor: hypervisor.map(str::to_string) };
+    }
+
+    Virtualization::BareMetal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_virtualization() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("proc/1")).unwrap();
+        assert_eq!(detect_virtualization(root.path()), Virtualization::BareMetal);
+
+        fs::write(
+            root.path().join("proc/version"),
+            "Linux version 5.15.153.1-microsoft-standard-WSL2 (root@1c602f52c2e4)\n",
+        )
+        .unwrap();
+        assert_eq!(detect_virtualization(root.path()), Virtualization::Wsl);
+
+        fs::write(root.path().join("proc/1/cgroup"), "0::/kubepods/besteffort/pod1234\n").unwrap();
+        assert_eq!(
+            detect_virtualization(root.path()),
+            Virtualization::Container { runtime: "kubepods".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_probe_container_and_ci() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join(".dockerenv"), "").unwrap();
+
+        let vars = |name: &str| match name {
+            "CI" | "GITHUB_ACTIONS" => Some("true".to_string()),
+            _ => None,
+        };
+        let environment = Environment::probe(root.path(), vars, true);
+
+        assert!(environment.in_container());
+        assert!(environment.is_root);
+        assert!(!environment.has_systemd);
+        assert_eq!(environment.ci.as_deref(), Some("github-actions"));
+        assert_eq!(environment.targets(root.path()), vec![InstallTarget::Host]);
+    }
+
+    #[test]
+    fn test_targets_on_a_workstation() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("run/systemd/system")).unwrap();
+
+        let environment = Environment::probe(root.path(), |_| None, false);
+        assert!(environment.has_systemd);
+        assert_eq!(environment.ci, None);
+        assert_eq!(environment.targets(root.path())[0], InstallTarget::Host);
+    }
+}
@@ -0,0 +1,519 @@
+use anyhow::{anyhow, Result};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+use crate::checkpoint::CheckpointStore;
+use crate::logging;
+use crate::report::StepStatus;
+use crate::steps::{Lock, Step, StepNotes};
+
+/// How often running tasks are redrawn while waiting for one to finish
+const TICK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Final outcome of a step
+#[derive(Debug)]
+pub struct StepOutcome {
+    pub status: StepStatus,
+    pub duration: Duration,
+    pub notes: StepNotes,
+}
+
+impl StepOutcome {
+    fn skipped(reason: impl Into<String>) -> Self {
+        Self {
+            status: StepStatus::Skipped(reason.into()),
+            duration: Duration::ZERO,
+            notes: StepNotes::default(),
+        }
+    }
+}
+
+/// Progress notifications for the UI
+pub enum Event<'a> {
+    /// A step started running on a worker
+    Started(&'a dyn Step),
+    /// A step finished, successfully or not
+    Finished(&'a dyn Step, &'a StepOutcome),
+    /// Nothing changed, but running tasks may want redrawing
+    Tick,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Pending,
+    Running,
+    /// Finished, successfully or because it was already satisfied
+    Succeeded,
+    /// Failed, or skipped because a dependency did not succeed
+    Failed,
+}
+
+/// Steps that can never start because they depend on themselves, directly
+/// or through other steps
+fn cyclic_steps(dependencies: &[Vec<usize>]) -> Vec<usize> {
+    let mut resolved = vec![false; dependencies.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (i, deps) in dependencies.iter().enumerate() {
+            if !resolved[i] && deps.iter().all(|&dep| resolved[dep]) {
+                resolved[i] = true;
+                changed = true;
+            }
+        }
+    }
+    (0..dependencies.len()).filter(|&i| !resolved[i]).collect()
+}
+
+/// Runs install steps as a dependency graph on the tokio blocking pool
+///
+/// Up to `jobs` steps run at once. A step starts once all of its
+/// dependencies succeeded and none of its locks are held by a running
+/// step. Steps whose dependencies failed are skipped. A graph with a
+/// cycle is rejected before anything runs.
+pub struct Executor {
+    jobs: usize,
+}
+
+impl Executor {
+    pub fn new(jobs: usize) -> Self {
+        Self { jobs: jobs.max(1) }
+    }
+
+    /// Run all steps, returning their outcomes in plan order
+    pub async fn run(
+        &self,
+        steps: Vec<Arc<dyn Step>>,
+        checkpoints: Arc<Mutex<CheckpointStore>>,
+        mut on_event: impl FnMut(Event) -> Result<()>,
+    ) -> Result<Vec<StepOutcome>> {
+        let ids: Vec<String> = steps.iter().map(|step| step.id()).collect();
+        let index: HashMap<&str, usize> = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (id.as_str(), i))
+            .collect();
+        let dependencies: Vec<Vec<usize>> = steps
+            .iter()
+            .map(|step| {
+                step.dependencies()
+                    .iter()
+                    .filter_map(|dep| index.get(dep.as_str()).copied())
+                    .collect()
+            })
+            .collect();
+
+        let cyclic = cyclic_steps(&dependencies);
+        if !cyclic.is_empty() {
+            let names: Vec<&str> = cyclic.iter().map(|&i| ids[i].as_str()).collect();
+            let message = format!("Dependency cycle between steps: {}", names.join(", "));
+            logging::error(&message);
+            return Err(anyhow!(message));
+        }
+
+        let mut state = vec![State::Pending; steps.len()];
+        let mut outcomes: Vec<Option<StepOutcome>> = steps.iter().map(|_| None).collect();
+        let mut held: HashSet<Lock> = HashSet::new();
+        let mut tasks = JoinSet::new();
+        let mut task_index = HashMap::new();
+        let mut ticker = tokio::time::interval(TICK_INTERVAL);
+
+        let scheduled: Result<()> = async {
+            loop {
+                // Skip anything downstream of a failure
+                let mut changed = true;
+                while changed {
+                    changed = false;
+                    for i in 0..steps.len() {
+                        if state[i] != State::Pending {
+                            continue;
+                        }
+                        let failed = dependencies[i]
+                            .iter()
+                            .find(|&&dep| state[dep] == State::Failed);
+                        if let Some(&dep) = failed {
+                            let outcome = StepOutcome::skipped(format!("{} did not succeed", ids[dep]));
+                            on_event(Event::Finished(steps[i].as_ref(), &outcome))?;
+                            outcomes[i] = Some(outcome);
+                            state[i] = State::Failed;
+                            changed = true;
+                        }
+                    }
+                }
+
+                // Start every step that is ready, in plan order
+                for i in 0..steps.len() {
+                    if tasks.len() >= self.jobs {
+                        break;
+                    }
+                    if state[i] != State::Pending {
+                        continue;
+                    }
+                    let ready = dependencies[i].iter().all(|&dep| state[dep] == State::Succeeded);
+                    let locks = steps[i].locks();
+                    if !ready || locks.iter().any(|lock| held.contains(lock)) {
+                        continue;
+                    }
+
+                    held.extend(locks);
+                    state[i] = State::Running;
+                    on_event(Event::Started(steps[i].as_ref()))?;
+
+                    let step = Arc::clone(&steps[i]);
+                    let checkpoints = Arc::clone(&checkpoints);
+                    let handle = tasks.spawn_blocking(move || execute_step(step.as_ref(), &checkpoints));
+                    task_index.insert(handle.id(), i);
+                }
+
+                if tasks.is_empty() {
+                    break;
+                }
+
+                let joined = tokio::select! {
+                    joined = tasks.join_next_with_id() => joined,
+                    _ = ticker.tick() => {
+                        on_event(Event::Tick)?;
+                        continue;
+                    }
+                };
+
+                let (i, outcome) = match joined {
+                    Some(Ok((id, outcome))) => (task_index[&id], outcome),
+                    Some(Err(e)) => {
+                        let i = task_index[&e.id()];
+                        let outcome = StepOutcome {
+                            status: StepStatus::Failed(format!("step panicked: {}", e)),
+                            duration: Duration::ZERO,
+                            notes: StepNotes::default(),
+                        };
+                        (i, outcome)
+                    }
+                    None => break,
+                };
+
+                for lock in steps[i].locks() {
+                    held.remove(&lock);
+                }
+                state[i] = match outcome.status {
+                    StepStatus::Failed(_) => State::Failed,
+                    _ => State::Succeeded,
+                };
+                on_event(Event::Finished(steps[i].as_ref(), &outcome))?;
+                outcomes[i] = Some(outcome);
+            }
+
+            Ok(())
+        }
+        .await;
+        if scheduled.is_err() {
+            // Running steps hold locks and write checkpoints; let them finish
+            while tasks.join_next().await.is_some() {}
+        }
+        scheduled?;
+
+        outcomes
+            .into_iter()
+            .map(|outcome| outcome.ok_or_else(|| anyhow!("Step finished without an outcome")))
+            .collect()
+    }
+}
+
+/// Run one step through check, apply and verify
+///
+/// Satisfied steps are skipped, which makes re-running a no-op. A step
+/// whose postcondition still fails after `apply` is an error.
+fn execute_step(step: &dyn Step, checkpoints: &Mutex<CheckpointStore>) -> StepOutcome {
+    let id = step.id();
+    let started = Instant::now();
+    let mut notes = StepNotes::default();
+
+    let result = run_phases(step, &id, checkpoints, &mut notes);
+    let status = match result {
+        Ok(None) => StepStatus::Success,
+        Ok(Some(reason)) => StepStatus::Skipped(reason.to_string()),
+        Err(e) => StepStatus::Failed(format!("{:#}", e)),
+    };
+    let duration = started.elapsed();
+
+    logging::record(
+        logging::Level::Info,
+        "step",
+        &[
+            ("id", id),
+            ("status", format!("{:?}", status)),
+            ("duration_ms", duration.as_millis().to_string()),
+        ],
+    );
+
+    StepOutcome {
+        status,
+        duration,
+        notes,
+    }
+}
+
+/// Returns the reason the step was skipped, if it was
+fn run_phases(
+    step: &dyn Step,
+    id: &str,
+    checkpoints: &Mutex<CheckpointStore>,
+    notes: &mut StepNotes,
+) -> Result<Option<&'static str>> {
+    let lock_checkpoints = || checkpoints.lock().unwrap_or_else(|e| e.into_inner());
+
+    let satisfied = step.check().unwrap_or_else(|e| {
+        logging::warn(format!("Check for step {} failed, applying it: {:#}", id, e));
+        false
+    });
+    if satisfied {
+        let mut checkpoints = lock_checkpoints();
+        let reason = if checkpoints.is_complete(id) {
+            "completed in a previous run"
+        } else {
+            "already satisfied"
+        };
+        logging::info(format!("Skipping step {}: {}", id, reason));
+        checkpoints.mark_complete(id)?;
+        return Ok(Some(reason));
+    }
+    lock_checkpoints().invalidate(id)?;
+
+    logging::info(format!("Applying step {}", id));
+    step.apply(notes)?;
+    if !step.verify()? {
+        return Err(anyhow!(
+            "'{}' did not take effect: postcondition still fails after apply",
+            step.name()
+        ));
+    }
+
+    lock_checkpoints().mark_complete(id)?;
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkpoint::SavedPlan;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// Step whose goal is a flag, with a configurable broken apply
+    struct FlagStep {
+        id: &'static str,
+        dependencies: Vec<&'static str>,
+        locks: Vec<Lock>,
+        done: AtomicBool,
+        applies: AtomicUsize,
+        broken: bool,
+        running: Option<Arc<AtomicUsize>>,
+        peak: Option<Arc<AtomicUsize>>,
+    }
+
+    impl FlagStep {
+        fn new(id: &'static str) -> Self {
+            Self {
+                id,
+                dependencies: vec![],
+                locks: vec![],
+                done: AtomicBool::new(false),
+                applies: AtomicUsize::new(0),
+                broken: false,
+                running: None,
+                peak: None,
+            }
+        }
+    }
+
+    impl Step for FlagStep {
+        fn id(&self) -> String {
+            self.id.to_string()
+        }
+
+        fn component(&self) -> &str {
+            "Test"
+        }
+
+        fn name(&self) -> String {
+            self.id.to_string()
+        }
+
+        fn dependencies(&self) -> Vec<String> {
+            self.dependencies.iter().map(|dep| dep.to_string()).collect()
+        }
+
+        fn locks(&self) -> Vec<Lock> {
+            self.locks.clone()
+        }
+
+        fn check(&self) -> Result<bool> {
+            Ok(self.done.load(Ordering::SeqCst))
+        }
+
+        fn apply(&self, _notes: &mut StepNotes) -> Result<()> {
+            self.applies.fetch_add(1, Ordering::SeqCst);
+            if let (Some(running), Some(peak)) = (&self.running, &self.peak) {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(50));
+                running.fetch_sub(1, Ordering::SeqCst);
+            }
+            self.done.store(!self.broken, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn store(dir: &tempfile::TempDir) -> Arc<Mutex<CheckpointStore>> {
+        let plan = SavedPlan {
+            target: None,
+            components: vec![],
+            accelerator: None,
+            pytorch_index: None,
+            rust: Default::default(),
+        };
+        let store = CheckpointStore::fresh(&dir.path().join("checkpoints.json"), plan).unwrap();
+        Arc::new(Mutex::new(store))
+    }
+
+    #[test]
+    fn test_execute_step_is_idempotent() {
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoints = store(&dir);
+        let step = FlagStep::new("test.flag");
+
+        let first = execute_step(&step, &checkpoints);
+        let second = execute_step(&step, &checkpoints);
+
+        assert_eq!(step.applies.load(Ordering::SeqCst), 1);
+        assert_eq!(first.status, StepStatus::Success);
+        assert_eq!(
+            second.status,
+            StepStatus::Skipped("completed in a previous run".to_string())
+        );
+    }
+
+    #[test]
+    fn test_execute_step_skips_satisfied() {
+        let dir = tempfile::tempdir().unwrap();
+        let step = FlagStep::new("test.flag");
+        step.done.store(true, Ordering::SeqCst);
+
+        let outcome = execute_step(&step, &store(&dir));
+
+        assert_eq!(step.applies.load(Ordering::SeqCst), 0);
+        assert_eq!(outcome.status, StepStatus::Skipped("already satisfied".to_string()));
+    }
+
+    #[test]
+    fn test_execute_step_fails_when_verify_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoints = store(&dir);
+        let mut step = FlagStep::new("test.flag");
+        step.broken = true;
+
+        let outcome = execute_step(&step, &checkpoints);
+
+        assert!(matches!(outcome.status, StepStatus::Failed(_)));
+        assert!(!checkpoints.lock().unwrap().is_complete("test.flag"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_run_respects_dependencies_and_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut broken = FlagStep::new("broken");
+        broken.broken = true;
+        let mut downstream = FlagStep::new("downstream");
+        downstream.dependencies = vec!["broken"];
+        let mut transitive = FlagStep::new("transitive");
+        transitive.dependencies = vec!["downstream"];
+        let independent = FlagStep::new("independent");
+
+        let steps: Vec<Arc<dyn Step>> = vec![
+            Arc::new(broken),
+            Arc::new(downstream),
+            Arc::new(transitive),
+            Arc::new(independent),
+        ];
+        let outcomes = Executor::new(4)
+            .run(steps, store(&dir), |_| Ok(()))
+            .await
+            .unwrap();
+
+        assert!(matches!(outcomes[0].status, StepStatus::Failed(_)));
+        assert_eq!(
+            outcomes[1].status,
+            StepStatus::Skipped("broken did not succeed".to_string())
+        );
+        assert_eq!(
+            outcomes[2].status,
+            StepStatus::Skipped("downstream did not succeed".to_string())
+        );
+        assert_eq!(outcomes[3].status, StepStatus::Success);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_run_parallelism_and_locks() {
+        let run = |locks: Vec<Lock>| async move {
+            let dir = tempfile::tempdir().unwrap();
+            let running = Arc::new(AtomicUsize::new(0));
+            let peak = Arc::new(AtomicUsize::new(0));
+            let steps: Vec<Arc<dyn Step>> = ["a", "b", "c"]
+                .into_iter()
+                .map(|id| {
+                    let mut step = FlagStep::new(id);
+                    step.locks = locks.clone();
+                    step.running = Some(Arc::clone(&running));
+                    step.peak = Some(Arc::clone(&peak));
+                    Arc::new(step) as Arc<dyn Step>
+                })
+                .collect();
+
+            Executor::new(3).run(steps, store(&dir), |_| Ok(())).await.unwrap();
+            peak.load(Ordering::SeqCst)
+        };
+
+        assert!(run(vec![]).await > 1);
+        assert_eq!(run(vec![Lock::PackageManager]).await, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_run_rejects_cycles() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut a = FlagStep::new("a");
+        a.dependencies = vec!["b"];
+        let mut b = FlagStep::new("b");
+        b.dependencies = vec!["a"];
+
+        let independent = Arc::new(FlagStep::new("independent"));
+
+        let steps: Vec<Arc<dyn Step>> = vec![Arc::new(a), Arc::new(b), independent.clone()];
+        let error = Executor::new(2)
+            .run(steps, store(&dir), |_| Ok(()))
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Dependency cycle between steps: a, b");
+        assert_eq!(independent.applies.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_run_waits_for_running_steps_on_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut slow = FlagStep::new("slow");
+        slow.running = Some(Arc::new(AtomicUsize::new(0)));
+        slow.peak = Some(Arc::new(AtomicUsize::new(0)));
+        let slow = Arc::new(slow);
+
+        let steps: Vec<Arc<dyn Step>> = vec![slow.clone(), Arc::new(FlagStep::new("next"))];
+        let result = Executor::new(2)
+            .run(steps, store(&dir), |event| match event {
+                Event::Started(step) if step.id() == "next" => Err(anyhow!("UI closed")),
+                _ => Ok(()),
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert!(slow.done.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,245 @@
+use anyhow::anyhow;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::cuda::{CudaStack, Version};
+use super::{GpuType, Vendor};
+
+const PYTORCH_INDEX: &str = "https://download.pytorch.org/whl";
+
+/// PyTorch wheel tags by the oldest CUDA release they run on, newest first
+const PYTORCH_CUDA_TAGS: &[(&str, &str)] = &[
+    ("12.6", "cu126"),
+    ("12.4", "cu124"),
+    ("12.1", "cu121"),
+    ("11.8", "cu118"),
+];
+
+/// PyTorch wheel tags by the oldest ROCm release they run on, newest first
+const PYTORCH_ROCM_TAGS: &[(&str, &str)] = &[
+    ("6.2", "rocm6.2"),
+    ("6.1", "rocm6.1"),
+    ("6.0", "rocm6.0"),
+    ("5.7", "rocm5.7"),
+];
+
+/// LLVM targets of the cards ROCm supports on Linux: Instinct MI100 to
+/// MI300 and Radeon RX 6900/7900 class cards. APU graphics like gfx1035
+/// and gfx1103 are missing on purpose.
+const ROCM_GFX_TARGETS: &[&str] = &["gfx908", "gfx90a", "gfx942", "gfx1030", "gfx1100", "gfx1101"];
+
+/// Hardware acceleration a component's packages are built for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Accelerator {
+    Cuda,
+    Rocm,
+    Metal,
+    Cpu,
+}
+
+impl Accelerator {
+    pub const ALL: [Accelerator; 4] = [
+        Accelerator::Cuda,
+        Accelerator::Rocm,
+        Accelerator::Metal,
+        Accelerator::Cpu,
+    ];
+
+    /// Best accelerator for the detected devices, preferring NVIDIA
+    ///
+    /// ROCm needs a discrete AMD card or one rocminfo reports a supported
+    /// target for; APU graphics and cards without a driver fall back to CPU.
+    pub fn for_devices(devices: &[GpuType]) -> Self {
+        let has = |vendor: Vendor| devices.iter().any(|device| device.vendor() == vendor);
+        let rocm_capable = devices.iter().any(|device| match device {
+            GpuType::Amd(gpu) => {
+                !gpu.integrated
+                    || gpu.gfx_target.as_deref().is_some_and(|target| ROCM_GFX_TARGETS.contains(&target))
+            }
+            _ => false,
+        });
+        if has(Vendor::Nvidia) {
+            Accelerator::Cuda
+        } else if rocm_capable {
+            Accelerator::Rocm
+        } else if devices
+            .iter()
+            .any(|device| matches!(device, GpuType::Metal { architecture, .. } if architecture == "Apple Silicon"))
+        {
+            Accelerator::Metal
+        } else {
+            Accelerator::Cpu
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Accelerator::Cuda => "cuda",
+            Accelerator::Rocm => "rocm",
+            Accelerator::Metal => "metal",
+            Accelerator::Cpu => "cpu",
+        }
+    }
+
+    /// PyTorch package index for this accelerator
+    ///
+    /// `runtime` is the newest CUDA release the driver supports, or the
+    /// installed ROCm release. Metal uses the default PyPI wheels.
+    pub fn pytorch_index_url(&self, runtime: Option<&Version>) -> Option<String> {
+        let pick = |tags: &[(&str, &'static str)], default: &'static str| {
+            runtime
+                .and_then(|runtime| {
+                    tags.iter()
+                        .find(|(oldest, _)| Version::parse(oldest).is_some_and(|oldest| *runtime >= oldest))
+                        .map(|(_, tag)| *tag)
+                })
+                .unwrap_or(default)
+        };
+
+        let tag = match self {
+            Accelerator::Cuda => pick(PYTORCH_CUDA_TAGS, "cu124"),
+            Accelerator::Rocm => pick(PYTORCH_ROCM_TAGS, "rocm6.1"),
+            Accelerator::Cpu => "cpu",
+            Accelerator::Metal => return None,
+        };
+        Some(format!("{}/{}", PYTORCH_INDEX, tag))
+    }
+}
+
+impl fmt::Display for Accelerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Accelerator::Cuda => write!(f, "CUDA (NVIDIA)"),
+            Accelerator::Rocm => write!(f, "ROCm (AMD)"),
+            Accelerator::Metal => write!(f, "Metal (Apple Silicon)"),
+            Accelerator::Cpu => write!(f, "CPU only"),
+        }
+    }
+}
+
+impl FromStr for Accelerator {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Accelerator::ALL
+            .into_iter()
+            .find(|accelerator| accelerator.as_str() == s)
+            .ok_or_else(|| anyhow!("Unknown accelerator: {}", s))
+    }
+}
+
+/// Accelerator choice for a plan, with the runtime versions behind it
+#[derive(Debug, Clone, Default)]
+pub struct Hardware {
+    pub devices: Vec<GpuType>,
+    /// Newest CUDA release the NVIDIA driver supports
+    pub cuda: Option<Version>,
+    /// Installed ROCm release
+    pub rocm: Option<Version>,
+}
+
+impl Hardware {
+    /// Detect devices and runtimes; failures fall back to CPU-only
+    pub fn detect() -> Self {
+        let devices = super::detect_gpu().unwrap_or_default();
+        let cuda = devices
+            .iter()
+            .any(|device| matches!(device, GpuType::Nvidia(_)))
+            .then(|| CudaStack::detect(Path::new("/")).ok())
+            .flatten()
+            .and_then(|stack| stack.max_toolkit());
+        let rocm = devices.iter().find_map(|device| match device {
+            GpuType::Amd(gpu) => gpu.rocm_version.as_deref().and_then(Version::parse),
+            _ => None,
+        });
+
+        Self { devices, cuda, rocm }
+    }
+
+    pub fn accelerator(&self) -> Accelerator {
+        Accelerator::for_devices(&self.devices)
+    }
+
+    pub fn pytorch_index_url(&self, accelerator: Accelerator) -> Option<String> {
+        let runtime = match accelerator {
+            Accelerator::Cuda => self.cuda.as_ref(),
+            Accelerator::Rocm => self.rocm.as_ref(),
+            Accelerator::Metal | Accelerator::Cpu => None,
+        };
+        accelerator.pytorch_index_url(runtime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::{AmdGpu, PciGpu};
+
+    #[test]
+    fn test_for_devices() {
+        assert_eq!(Accelerator::for_devices(&[]), Accelerator::Cpu);
+
+        let amd = GpuType::Amd(AmdGpu {
+            name: "AMD Instinct MI250X".to_string(),
+            device_id: 0x740c,
+            pci_bus_id: None,
+            integrated: false,
+            memory_bytes: None,
+            gfx_target: Some("gfx90a".to_string()),
+            rocm_version: None,
+        });
+        assert_eq!(Accelerator::for_devices(&[amd]), Accelerator::Rocm);
+
+        // A Ryzen laptop's Radeon 680M, even with ROCm installed
+        let apu = AmdGpu {
+            name: "AMD Radeon 680M".to_string(),
+            device_id: 0x1681,
+            pci_bus_id: Some("0000:e5:00.0".to_string()),
+            integrated: true,
+            memory_bytes: Some(536870912),
+            gfx_target: Some("gfx1035".to_string()),
+            rocm_version: Some("6.1.2".to_string()),
+        };
+        assert_eq!(Accelerator::for_devices(&[GpuType::Amd(apu.clone())]), Accelerator::Cpu);
+        // Missing from the discrete list, but a target ROCm supports
+        let unlisted = AmdGpu { gfx_target: Some("gfx1100".to_string()), ..apu };
+        assert_eq!(Accelerator::for_devices(&[GpuType::Amd(unlisted)]), Accelerator::Rocm);
+
+        let no_driver = GpuType::DriverMissing(PciGpu {
+            slot: "0000:c1:00.0".to_string(),
+            vendor_id: 0x1002,
+            device_id: 0x74a1,
+            driver: None,
+        });
+        assert_eq!(Accelerator::for_devices(&[no_driver]), Accelerator::Cpu);
+
+        let apple = GpuType::Metal {
+            device_name: "Apple M2 Max".to_string(),
+            architecture: "Apple Silicon".to_string(),
+        };
+        assert_eq!(Accelerator::for_devices(&[apple]), Accelerator::Metal);
+    }
+
+    #[test]
+    fn test_pytorch_index_url() {
+        let version = |v: &str| Version::parse(v).unwrap();
+        assert_eq!(
+            Accelerator::Cuda.pytorch_index_url(Some(&version("12.5"))).as_deref(),
+            Some("https://download.pytorch.org/whl/cu124")
+        );
+        assert_eq!(
+            Accelerator::Cuda.pytorch_index_url(Some(&version("12.2"))).as_deref(),
+            Some("https://download.pytorch.org/whl/cu121")
+        );
+        assert_eq!(
+            Accelerator::Rocm.pytorch_index_url(Some(&version("6.1.2"))).as_deref(),
+            Some("https://download.pytorch.org/whl/rocm6.1")
+        );
+        assert_eq!(
+            Accelerator::Cpu.pytorch_index_url(None).as_deref(),
+            Some("https://download.pytorch.org/whl/cpu")
+        );
+        assert_eq!(Accelerator::Metal.pytorch_index_url(None), None);
+    }
+}
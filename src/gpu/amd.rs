@@ -0,0 +1,172 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use which::which;
+
+use super::pci;
+use super::sysfs::{self, VENDOR_AMD};
+
+/// A single AMD device, from sysfs and rocminfo
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AmdGpu {
+    pub name: String,
+    pub device_id: u16,
+    pub pci_bus_id: Option<String>,
+    /// Radeon graphics on an APU rather than a discrete card
+    pub integrated: bool,
+    /// VRAM in bytes, as reported by the amdgpu driver
+    pub memory_bytes: Option<u64>,
+    /// LLVM target, e.g. "gfx90a"; needs ROCm
+    pub gfx_target: Option<String>,
+    /// Installed ROCm release, e.g. "6.1.2"
+    pub rocm_version: Option<String>,
+}
+
+/// A GPU agent listed by rocminfo
+#[derive(Debug, Clone, PartialEq)]
+pub struct RocmAgent {
+    pub gfx_target: String,
+    pub marketing_name: Option<String>,
+}
+
+/// List AMD devices below `root`, which is `/` outside of tests
+pub fn detect(root: &Path) -> Result<Vec<AmdGpu>> {
+    let agents = if which("rocminfo").is_ok() {
+        let output = Command::new("rocminfo").output()?;
+        if output.status.success() {
+            parse_rocminfo(&String::from_utf8_lossy(&output.stdout))
+        } else {
+            Vec::new()
+        }
+    } else {
+        Vec::new()
+    };
+
+    Ok(merge(root, &agents, rocm_version(root)))
+}
+
+/// Combine sysfs cards with rocminfo agents, which list devices in the same order
+pub(super) fn merge(root: &Path, agents: &[RocmAgent], rocm_version: Option<String>) -> Vec<AmdGpu> {
+    let cards: Vec<sysfs::DrmCard> = sysfs::drm_cards(root)
+        .into_iter()
+        .filter(|card| card.vendor_id == VENDOR_AMD)
+        .collect();
+
+    (0..cards.len().max(agents.len()))
+        .map(|i| {
+            let card = cards.get(i);
+            let agent = agents.get(i);
+            let device_id = card.map_or(0, |card| card.device_id);
+
+            let name = agent
+                .and_then(|agent| agent.marketing_name.clone())
+                .or_else(|| card.and_then(|card| sysfs::read_attr(&card.device_dir.join("product_name"))))
+                .unwrap_or_else(|| pci::model_name(VENDOR_AMD, device_id));
+
+            AmdGpu {
+                name,
+                device_id,
+                pci_bus_id: card.and_then(|card| card.pci_slot.clone()),
+                integrated: card.is_some_and(|card| !pci::is_discrete(VENDOR_AMD, card.device_id)),
+                memory_bytes: card
+                    .and_then(|card| sysfs::read_attr(&card.device_dir.join("mem_info_vram_total")))
+                    .and_then(|bytes| bytes.parse().ok()),
+                gfx_target: agent.map(|agent| agent.gfx_target.clone()),
+                rocm_version: rocm_version.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Parse the GPU agents out of `rocminfo` output
+///
+/// Nested sections such as "ISA Info" repeat keys like `Name`, so only
+/// the first occurrence of each key within an agent counts.
+pub fn parse_rocminfo(output: &str) -> Vec<RocmAgent> {
+    let mut agents = Vec::new();
+
+    for section in output.split("*******").skip(1) {
+        let field = |key: &str| {
+            section.lines().find_map(|line| {
+                line.trim()
+                    .strip_prefix(key)
+                    .and_then(|rest| rest.strip_prefix(':'))
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+            })
+        };
+
+        if field("Device Type").as_deref() != Some("GPU") {
+            continue;
+        }
+        if let Some(gfx_target) = field("Name") {
+            agents.push(RocmAgent {
+                gfx_target,
+                marketing_name: field("Marketing Name"),
+            });
+        }
+    }
+
+    agents
+}
+
+/// Installed ROCm release, read from `/opt/rocm/.info/version`
+pub fn rocm_version(root: &Path) -> Option<String> {
+    let contents = fs::read_to_string(root.join("opt/rocm/.info/version")).ok()?;
+    // Drop the build number, e.g. "6.1.2-119" -> "6.1.2"
+    let version = contents.trim().split('-').next()?.to_string();
+    (!version.is_empty()).then_some(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::sysfs::testing::add_card;
+
+    #[test]
+    fn test_parse_rocminfo_skips_cpu_agents() {
+        let agents = parse_rocminfo(include_str!("fixtures/rocminfo-2x-mi250x.txt"));
+        assert_eq!(agents.len(), 2);
+        assert_eq!(agents[0].gfx_target, "gfx90a");
+        assert_eq!(agents[1].marketing_name.as_deref(), Some("AMD Instinct MI250X"));
+    }
+
+    #[test]
+    fn test_detect_from_sysfs_and_rocm() {
+        let root = tempfile::tempdir().unwrap();
+        let device = add_card(root.path(), "card0", "0x1002", "0x740c", "0000:c1:00.0");
+        fs::write(device.join("mem_info_vram_total"), "68702699520\n").unwrap();
+        add_card(root.path(), "card1", "0x8086", "0x56a0", "0000:03:00.0");
+
+        // Without ROCm only sysfs information is available
+        let gpus = merge(root.path(), &[], rocm_version(root.path()));
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].name, "Advanced Micro Devices, Inc. [AMD/ATI] Aldebaran/MI200 [Instinct MI250X/MI250]");
+        assert_eq!(gpus[0].memory_bytes, Some(68702699520));
+        assert!(!gpus[0].integrated);
+        assert_eq!(gpus[0].rocm_version, None);
+
+        let info = root.path().join("opt/rocm/.info");
+        fs::create_dir_all(&info).unwrap();
+        fs::write(info.join("version"), "6.1.2-119\n").unwrap();
+
+        let agents = parse_rocminfo(include_str!("fixtures/rocminfo-2x-mi250x.txt"));
+        let gpus = merge(root.path(), &agents[..1], rocm_version(root.path()));
+        assert_eq!(gpus[0].name, "AMD Instinct MI250X");
+        assert_eq!(gpus[0].pci_bus_id.as_deref(), Some("0000:c1:00.0"));
+        assert_eq!(gpus[0].gfx_target.as_deref(), Some("gfx90a"));
+        assert_eq!(gpus[0].rocm_version.as_deref(), Some("6.1.2"));
+    }
+
+    #[test]
+    fn test_detect_marks_apu_graphics() {
+        let root = tempfile::tempdir().unwrap();
+        add_card(root.path(), "card0", "0x1002", "0x1681", "0000:e5:00.0");
+
+        let gpus = merge(root.path(), &[], None);
+        assert_eq!(gpus[0].name, "Advanced Micro Devices, Inc. [AMD/ATI] Rembrandt [Radeon 680M]");
+        assert!(gpus[0].integrated);
+    }
+}
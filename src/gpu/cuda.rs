@@ -0,0 +1,410 @@
+use anyhow::Result;
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use which::which;
+
+/// Minimum Linux driver for each CUDA toolkit release
+const TOOLKIT_MIN_DRIVER: &[(&str, &str)] = &[
+    ("11.0", "450.36.06"),
+    ("11.1", "455.23"),
+    ("11.2", "460.27.03"),
+    ("11.3", "465.19.01"),
+    ("11.4", "470.42.01"),
+    ("11.5", "495.29.05"),
+    ("11.6", "510.39.01"),
+    ("11.7", "515.43.04"),
+    ("11.8", "520.61.05"),
+    ("12.0", "525.60.13"),
+    ("12.1", "530.30.02"),
+    ("12.2", "535.54.03"),
+    ("12.3", "545.23.06"),
+    ("12.4", "550.54.14"),
+    ("12.5", "555.42.02"),
+    ("12.6", "560.28.03"),
+    ("12.8", "570.26"),
+];
+
+/// cuDNN releases and the CUDA toolkits they support:
+/// (first cuDNN, last cuDNN, oldest CUDA, newest CUDA)
+const CUDNN_CUDA: &[(&str, &str, &str, &str)] = &[
+    ("8.0", "8.8", "10.2", "11.8"),
+    ("8.9", "8.9", "11.0", "12.2"),
+    ("9.0", "9.99", "11.8", "12.99"),
+];
+
+/// Headers that carry the cuDNN version defines, newest layout first
+const CUDNN_HEADERS: &[&str] = &[
+    "usr/include/cudnn_version.h",
+    "usr/include/x86_64-linux-gnu/cudnn_version_v9.h",
+    "usr/include/x86_64-linux-gnu/cudnn_version_v8.h",
+    "usr/local/cuda/include/cudnn_version.h",
+    "usr/include/cudnn.h",
+];
+
+/// A dotted version number such as "550.54.14" or "12.4"
+///
+/// The original text is kept for display, since driver versions like
+/// "535.54.03" have meaningful leading zeros.
+#[derive(Debug, Clone)]
+pub struct Version {
+    parts: Vec<u32>,
+    text: String,
+}
+
+impl Version {
+    pub fn parse(value: &str) -> Option<Self> {
+        let text = value.trim().trim_start_matches('V');
+        let parts = text
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<Vec<u32>>>()?;
+        Some(Self { parts, text: text.to_string() })
+    }
+
+    pub fn major(&self) -> u32 {
+        self.parts[0]
+    }
+
+    /// Drop everything after the minor version, e.g. "12.4.131" -> "12.4"
+    pub fn major_minor(&self) -> Self {
+        let minor = self.parts.get(1).copied().unwrap_or(0);
+        Self {
+            parts: vec![self.major(), minor],
+            text: format!("{}.{}", self.major(), minor),
+        }
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Missing components count as zero, so "12.4" == "12.4.0"
+        let len = self.parts.len().max(other.parts.len());
+        let pad = |v: &Self| {
+            v.parts.iter().copied().chain(std::iter::repeat(0)).take(len).collect::<Vec<_>>()
+        };
+        pad(self).cmp(&pad(other))
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// The installed NVIDIA driver, CUDA toolkit and cuDNN
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CudaStack {
+    pub driver: Option<Version>,
+    /// Newest CUDA release the driver supports, from nvidia-smi
+    pub driver_cuda: Option<Version>,
+    pub toolkit: Option<Version>,
+    pub cudnn: Option<Version>,
+}
+
+/// A problem with the CUDA stack, with enough detail to fix it
+#[derive(Debug, Clone, PartialEq)]
+pub enum CudaIssue {
+    /// No toolkit; install one no newer than `max_toolkit`
+    ToolkitMissing { max_toolkit: Option<Version> },
+    /// The toolkit needs a newer driver than the one installed
+    ToolkitTooNew {
+        toolkit: Version,
+        driver: Version,
+        required_driver: Option<Version>,
+        max_toolkit: Option<Version>,
+    },
+    /// cuDNN was built for a different range of CUDA releases
+    CudnnMismatch {
+        cudnn: Version,
+        toolkit: Version,
+        supported: (Version, Version),
+    },
+}
+
+impl fmt::Display for CudaIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CudaIssue::ToolkitMissing { max_toolkit } => {
+                write!(f, "CUDA toolkit is not installed; install it for GPU acceleration")?;
+                if let Some(max) = max_toolkit {
+                    write!(f, " (the driver supports up to CUDA {})", max)?;
+                }
+                Ok(())
+            }
+            CudaIssue::ToolkitTooNew { toolkit, driver, required_driver, max_toolkit } => {
+                write!(f, "CUDA toolkit {} is newer than NVIDIA driver {} supports; ", toolkit, driver)?;
+                match (required_driver, max_toolkit) {
+                    (Some(required), Some(max)) => write!(
+                        f,
+                        "upgrade the driver to {} or newer, or install CUDA toolkit {}",
+                        required, max
+                    ),
+                    (Some(required), None) => write!(f, "upgrade the driver to {} or newer", required),
+                    (None, _) => write!(f, "upgrade the driver"),
+                }
+            }
+            CudaIssue::CudnnMismatch { cudnn, toolkit, supported: (oldest, newest) } => write!(
+                f,
+                "cuDNN {} supports CUDA {} to {} but CUDA toolkit {} is installed; \
+                 install the cuDNN build for CUDA {}",
+                cudnn,
+                oldest,
+                newest,
+                toolkit,
+                toolkit.major()
+            ),
+        }
+    }
+}
+
+impl CudaStack {
+    /// Inspect the CUDA stack below `root`, which is `/` outside of tests
+    pub fn detect(root: &Path) -> Result<Self> {
+        let mut stack = Self::default();
+
+        if let Some(smi) = super::nvidia::smi_path() {
+            let output = Command::new(smi).output()?;
+            if output.status.success() {
+                let (driver, driver_cuda) = parse_smi_summary(&String::from_utf8_lossy(&output.stdout));
+                stack.driver = driver;
+                stack.driver_cuda = driver_cuda;
+            }
+        }
+
+        stack.toolkit = toolkit_from_json(root);
+        if stack.toolkit.is_none() {
+            let nvcc = which("nvcc").ok().or_else(|| {
+                let bundled = root.join("usr/local/cuda/bin/nvcc");
+                bundled.exists().then_some(bundled)
+            });
+            if let Some(nvcc) = nvcc {
+                let output = Command::new(nvcc).arg("--version").output()?;
+                stack.toolkit = parse_nvcc_version(&String::from_utf8_lossy(&output.stdout));
+            }
+        }
+
+        stack.cudnn = CUDNN_HEADERS
+            .iter()
+            .find_map(|header| fs::read_to_string(root.join(header)).ok())
+            .and_then(|contents| parse_cudnn_header(&contents));
+
+        Ok(stack)
+    }
+
+    /// Newest toolkit the installed driver can run
+    pub fn max_toolkit(&self) -> Option<Version> {
+        if let Some(cuda) = &self.driver_cuda {
+            return Some(cuda.major_minor());
+        }
+        let driver = self.driver.as_ref()?;
+        TOOLKIT_MIN_DRIVER
+            .iter()
+            .rev()
+            .find(|(_, min_driver)| Version::parse(min_driver).is_some_and(|min| *driver >= min))
+            .and_then(|(toolkit, _)| Version::parse(toolkit))
+    }
+
+    /// Compare the installed versions against the compatibility matrix
+    pub fn check(&self) -> Vec<CudaIssue> {
+        let mut issues = Vec::new();
+        let max_toolkit = self.max_toolkit();
+
+        let toolkit = match &self.toolkit {
+            Some(toolkit) => toolkit,
+            None => {
+                issues.push(CudaIssue::ToolkitMissing { max_toolkit });
+                return issues;
+            }
+        };
+
+        if let (Some(driver), Some(max)) = (&self.driver, &max_toolkit) {
+            if toolkit.major_minor() > *max {
+                let required_driver = TOOLKIT_MIN_DRIVER
+                    .iter()
+                    .find(|(release, _)| Version::parse(release).as_ref() == Some(&toolkit.major_minor()))
+                    .and_then(|(_, min_driver)| Version::parse(min_driver));
+                issues.push(CudaIssue::ToolkitTooNew {
+                    toolkit: toolkit.clone(),
+                    driver: driver.clone(),
+                    required_driver,
+                    max_toolkit: max_toolkit.clone(),
+                });
+            }
+        }
+
+        if let Some(cudnn) = &self.cudnn {
+            let cudnn_release = cudnn.major_minor();
+            let row = CUDNN_CUDA.iter().find(|(first, last, _, _)| {
+                let in_range = |bound: &str, ordering: Ordering| {
+                    Version::parse(bound).is_some_and(|bound| cudnn_release.cmp(&bound) != ordering)
+                };
+                in_range(first, Ordering::Less) && in_range(last, Ordering::Greater)
+            });
+
+            if let Some((_, _, oldest, newest)) = row {
+                let (oldest, newest) = (Version::parse(oldest), Version::parse(newest));
+                if let (Some(oldest), Some(newest)) = (oldest, newest) {
+                    let release = toolkit.major_minor();
+                    if release < oldest || release > newest {
+                        issues.push(CudaIssue::CudnnMismatch {
+                            cudnn: cudnn.clone(),
+                            toolkit: toolkit.clone(),
+                            supported: (oldest, newest),
+                        });
+                    }
+                }
+            }
+        }
+
+        issues
+    }
+}
+
+/// Driver version and supported CUDA release from the plain `nvidia-smi` table
+pub fn parse_smi_summary(output: &str) -> (Option<Version>, Option<Version>) {
+    let value_after = |label: &str| {
+        output
+            .find(label)
+            .and_then(|start| output[start + label.len()..].split_whitespace().next())
+            .and_then(Version::parse)
+    };
+    (value_after("Driver Version:"), value_after("CUDA Version:"))
+}
+
+/// Toolkit version from `nvcc --version`, e.g. "Cuda compilation tools, release 12.4, V12.4.131"
+pub fn parse_nvcc_version(output: &str) -> Option<Version> {
+    let line = output.lines().find(|line| line.contains("release"))?;
+    line.split(", ")
+        .find_map(|part| part.strip_prefix('V').and_then(Version::parse))
+        .or_else(|| {
+            line.split_once("release ")
+                .and_then(|(_, rest)| rest.split(',').next())
+                .and_then(Version::parse)
+        })
+}
+
+/// Toolkit version from `/usr/local/cuda/version.json`
+fn toolkit_from_json(root: &Path) -> Option<Version> {
+    let contents = fs::read_to_string(root.join("usr/local/cuda/version.json")).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&contents).ok()?;
+    json["cuda"]["version"].as_str().and_then(Version::parse)
+}
+
+/// cuDNN version from the `CUDNN_MAJOR`/`MINOR`/`PATCHLEVEL` defines
+pub fn parse_cudnn_header(contents: &str) -> Option<Version> {
+    let define = |name: &str| {
+        contents.lines().find_map(|line| {
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next(), words.next()) {
+                (Some("#define"), Some(key), Some(value)) if key == name => value.parse::<u32>().ok(),
+                _ => None,
+            }
+        })
+    };
+    let version = format!(
+        "{}.{}.{}",
+        define("CUDNN_MAJOR")?,
+        define("CUDNN_MINOR")?,
+        define("CUDNN_PATCHLEVEL").unwrap_or(0)
+    );
+    Version::parse(&version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(value: &str) -> Version {
+        Version::parse(value).unwrap()
+    }
+
+    #[test]
+    fn test_parse_tool_output() {
+        let (driver, cuda) = parse_smi_summary(include_str!("fixtures/nvidia-smi-summary.txt"));
+        assert_eq!(driver, Some(version("550.54.14")));
+        assert_eq!(cuda, Some(version("12.4")));
+
+        let nvcc = "nvcc: NVIDIA (R) Cuda compiler driver\n\
+                    Cuda compilation tools, release 12.4, V12.4.131\n\
+                    Build cuda_12.4.r12.4/compiler.33961263_0\n";
+        assert_eq!(parse_nvcc_version(nvcc), Some(version("12.4.131")));
+
+        let header = "#define CUDNN_MAJOR 9\n#define CUDNN_MINOR 1\n#define CUDNN_PATCHLEVEL 0\n\
+                      #define CUDNN_VERSION (CUDNN_MAJOR * 10000 + CUDNN_MINOR * 100 + CUDNN_PATCHLEVEL)\n";
+        assert_eq!(parse_cudnn_header(header), Some(version("9.1.0")));
+    }
+
+    #[test]
+    fn test_toolkit_from_json() {
+        let root = tempfile::tempdir().unwrap();
+        let cuda = root.path().join("usr/local/cuda");
+        fs::create_dir_all(&cuda).unwrap();
+        fs::write(
+            cuda.join("version.json"),
+            r#"{"cuda": {"name": "CUDA SDK", "version": "12.6.2"}}"#,
+        )
+        .unwrap();
+        assert_eq!(toolkit_from_json(root.path()), Some(version("12.6.2")));
+    }
+
+    #[test]
+    fn test_check_toolkit_newer_than_driver() {
+        let stack = CudaStack {
+            driver: Some(version("535.54.03")),
+            driver_cuda: None,
+            toolkit: Some(version("12.6.2")),
+            cudnn: None,
+        };
+        assert_eq!(stack.max_toolkit(), Some(version("12.2")));
+
+        let issues = stack.check();
+        assert_eq!(
+            issues,
+            vec![CudaIssue::ToolkitTooNew {
+                toolkit: version("12.6.2"),
+                driver: version("535.54.03"),
+                required_driver: Some(version("560.28.03")),
+                max_toolkit: Some(version("12.2")),
+            }]
+        );
+        assert!(issues[0].to_string().contains("upgrade the driver to 560.28.03"));
+    }
+
+    #[test]
+    fn test_check_cudnn_and_missing_toolkit() {
+        let stack = CudaStack {
+            driver: Some(version("550.54.14")),
+            driver_cuda: Some(version("12.4")),
+            toolkit: Some(version("12.4.131")),
+            cudnn: Some(version("8.6.0")),
+        };
+        assert!(matches!(stack.check().as_slice(), [CudaIssue::CudnnMismatch { .. }]));
+
+        let compatible = CudaStack { cudnn: Some(version("9.1.0")), ..stack.clone() };
+        assert_eq!(compatible.check(), vec![]);
+
+        let missing = CudaStack { toolkit: None, ..stack };
+        assert_eq!(
+            missing.check(),
+            vec![CudaIssue::ToolkitMissing { max_toolkit: Some(version("12.4")) }]
+        );
+    }
+}
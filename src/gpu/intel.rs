@@ -0,0 +1,124 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use super::pci;
+use super::sysfs::{self, VENDOR_INTEL};
+
+/// Directories searched for the level-zero loader
+const LIBRARY_DIRS: &[&str] = &[
+    "usr/lib/x86_64-linux-gnu",
+    "usr/lib/aarch64-linux-gnu",
+    "usr/lib64",
+    "usr/lib",
+    "usr/local/lib",
+];
+
+/// A single Intel device, from sysfs
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IntelGpu {
+    pub name: String,
+    pub device_id: u16,
+    pub pci_bus_id: Option<String>,
+    /// UHD/Iris graphics rather than an Arc or data center card
+    pub integrated: bool,
+    /// Installed level-zero loader version, e.g. "1.17.6"
+    pub level_zero_version: Option<String>,
+    /// Installed oneAPI release, e.g. "2024.1"
+    pub oneapi_version: Option<String>,
+}
+
+/// List Intel devices below `root`, which is `/` outside of tests
+pub fn detect(root: &Path) -> Vec<IntelGpu> {
+    let level_zero_version = level_zero_version(root);
+    let oneapi_version = oneapi_version(root);
+
+    sysfs::drm_cards(root)
+        .into_iter()
+        .filter(|card| card.vendor_id == VENDOR_INTEL)
+        .map(|card| IntelGpu {
+            name: pci::model_name(VENDOR_INTEL, card.device_id),
+            device_id: card.device_id,
+            pci_bus_id: card.pci_slot,
+            integrated: !pci::is_discrete(VENDOR_INTEL, card.device_id),
+            level_zero_version: level_zero_version.clone(),
+            oneapi_version: oneapi_version.clone(),
+        })
+        .collect()
+}
+
+/// Version of the level-zero loader, taken from `libze_loader.so.X.Y.Z`
+pub fn level_zero_version(root: &Path) -> Option<String> {
+    LIBRARY_DIRS
+        .iter()
+        .filter_map(|dir| fs::read_dir(root.join(dir)).ok())
+        .flat_map(|entries| entries.filter_map(|entry| entry.ok()))
+        .filter_map(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix("libze_loader.so."))
+                .map(str::to_string)
+        })
+        // The fully versioned name is the longest of the symlink chain
+        .max_by_key(|version| version.len())
+}
+
+/// Installed oneAPI release, from the `compiler/latest` symlink
+pub fn oneapi_version(root: &Path) -> Option<String> {
+    let oneapi = root.join("opt/intel/oneapi");
+    if !oneapi.is_dir() {
+        return None;
+    }
+
+    let version = fs::read_link(oneapi.join("compiler/latest"))
+        .ok()
+        .and_then(|target| target.file_name().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "unknown".to_string());
+    Some(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::sysfs::testing::add_card;
+
+    #[test]
+    fn test_detect_with_runtimes() {
+        let root = tempfile::tempdir().unwrap();
+        add_card(root.path(), "card0", "0x8086", "0x56a0", "0000:03:00.0");
+        add_card(root.path(), "card1", "0x1002", "0x740c", "0000:c1:00.0");
+
+        let gpus = detect(root.path());
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].name, "Intel Corporation DG2 [Arc A770]");
+        assert!(!gpus[0].integrated);
+        assert_eq!(gpus[0].level_zero_version, None);
+        assert_eq!(gpus[0].oneapi_version, None);
+
+        let lib = root.path().join("usr/lib/x86_64-linux-gnu");
+        fs::create_dir_all(&lib).unwrap();
+        for name in ["libze_loader.so.1", "libze_loader.so.1.17.6"] {
+            fs::write(lib.join(name), "").unwrap();
+        }
+        let compiler = root.path().join("opt/intel/oneapi/compiler");
+        fs::create_dir_all(compiler.join("2024.1")).unwrap();
+        std::os::unix::fs::symlink("2024.1", compiler.join("latest")).unwrap();
+
+        let gpus = detect(root.path());
+        assert_eq!(gpus[0].level_zero_version.as_deref(), Some("1.17.6"));
+        assert_eq!(gpus[0].oneapi_version.as_deref(), Some("2024.1"));
+    }
+
+    #[test]
+    fn test_detect_marks_integrated_graphics() {
+        let root = tempfile::tempdir().unwrap();
+        add_card(root.path(), "card0", "0x8086", "0x9a49", "0000:00:02.0");
+        add_card(root.path(), "card1", "0x8086", "0x56a5", "0000:03:00.0");
+
+        let gpus = detect(root.path());
+        assert_eq!(gpus[0].name, "Intel Corporation TigerLake-LP GT2 [Iris Xe Graphics]");
+        assert!(gpus[0].integrated);
+        assert!(!gpus[1].integrated);
+    }
+}
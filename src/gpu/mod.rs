@@ -0,0 +1,348 @@
+pub mod accelerator;
+pub mod amd;
+pub mod cuda;
+pub mod intel;
+pub mod nvidia;
+pub mod pci;
+pub mod sysfs;
+
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+
+use crate::environment::Environment;
+use crate::logging::{self, Level};
+
+pub use accelerator::{Accelerator, Hardware};
+pub use amd::AmdGpu;
+pub use cuda::{CudaIssue, CudaStack};
+pub use intel::IntelGpu;
+pub use nvidia::NvidiaGpu;
+pub use pci::PciGpu;
+
+/// GPU vendor, derived from the PCI vendor id
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vendor {
+    Nvidia,
+    Amd,
+    Intel,
+    Apple,
+    Other(u16),
+}
+
+impl Vendor {
+    pub fn from_id(id: u16) -> Self {
+        match id {
+            sysfs::VENDOR_NVIDIA => Vendor::Nvidia,
+            sysfs::VENDOR_AMD => Vendor::Amd,
+            sysfs::VENDOR_INTEL => Vendor::Intel,
+            other => Vendor::Other(other),
+        }
+    }
+}
+
+/// A single GPU device
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GpuType {
+    Nvidia(NvidiaGpu),
+    Amd(AmdGpu),
+    Intel(IntelGpu),
+    Metal {
+        device_name: String,
+        architecture: String,
+    },
+    /// On the PCI bus, but without a driver the vendor tools can use
+    DriverMissing(PciGpu),
+}
+
+impl GpuType {
+    pub fn vendor(&self) -> Vendor {
+        match self {
+            GpuType::Nvidia(_) => Vendor::Nvidia,
+            GpuType::Amd(_) => Vendor::Amd,
+            GpuType::Intel(_) => Vendor::Intel,
+            GpuType::Metal { .. } => Vendor::Apple,
+            GpuType::DriverMissing(gpu) => gpu.vendor(),
+        }
+    }
+}
+
+/// Detect all GPU devices; an empty list means CPU-only
+pub fn detect_gpu() -> Result<Vec<GpuType>> {
+    let root = Path::new("/");
+    let mut devices: Vec<GpuType> = nvidia::detect()?.into_iter().map(GpuType::Nvidia).collect();
+    devices.extend(amd::detect(root)?.into_iter().map(GpuType::Amd));
+    devices.extend(intel::detect(root).into_iter().map(GpuType::Intel));
+    devices.extend(missing_drivers(root, &devices).into_iter().map(GpuType::DriverMissing));
+    if !devices.is_empty() {
+        return Ok(devices);
+    }
+
+    // Check for Metal (macOS)
+    if cfg!(target_os = "macos") {
+        // system_profiler requires no special permissions on macOS
+        let output = Command::new("system_profiler")
+            .arg("SPDisplaysDataType")
+            .output()?;
+
+        if output.status.success() {
+            let info = String::from_utf8_lossy(&output.stdout);
+            
+            // Parse the Metal GPU info
+            if let Some(gpu_line) = info.lines()
+                .find(|line| line.contains("Chipset Model:")) {
+                let device_name = gpu_line
+                    .split(":")
+                    .nth(1)
+                    .map(|s| s.trim())
+                    .unwrap_or("Unknown")
+                    .to_string();
+
+                // Get architecture (Apple Silicon vs Intel)
+                let arch = if device_name.contains("Apple") {
+                    "Apple Silicon"
+                } else {
+                    "Intel"
+                };
+
+                return Ok(vec![GpuType::Metal {
+                    device_name,
+                    architecture: arch.to_string(),
+                }]);
+            }
+        }
+    }
+
+    Ok(Vec::new())
+}
+
+/// PCI display controllers that no vendor tool reported
+///
+/// NVIDIA cards only count once nvidia-smi can see them, since nouveau
+/// offers no CUDA. AMD and Intel cards just need a bound kernel driver.
+fn missing_drivers(root: &Path, detected: &[GpuType]) -> Vec<PciGpu> {
+    let detected_slots: Vec<&str> = detected
+        .iter()
+        .filter_map(|device| match device {
+            GpuType::Nvidia(gpu) => Some(gpu.pci_bus_id.as_str()),
+            GpuType::Amd(gpu) => gpu.pci_bus_id.as_deref(),
+            GpuType::Intel(gpu) => gpu.pci_bus_id.as_deref(),
+            _ => None,
+        })
+        .collect();
+
+    pci::scan(root)
+        .into_iter()
+        .filter(|gpu| !detected_slots.iter().any(|slot| pci::same_slot(slot, &gpu.slot)))
+        .filter(|gpu| match gpu.vendor() {
+            Vendor::Nvidia => true,
+            Vendor::Amd | Vendor::Intel => gpu.driver.is_none(),
+            // Virtual display adapters and the like are not worth reporting
+            Vendor::Apple | Vendor::Other(_) => false,
+        })
+        .collect()
+}
+
+/// How to get a driver for a GPU that has none
+///
+/// Containers use the host's driver, so there the fix is passthrough.
+pub fn driver_hint(vendor: Vendor, environment: &Environment) -> &'static str {
+    if environment.in_container() {
+        return match vendor {
+            Vendor::Nvidia => "install the NVIDIA Container Toolkit on the host and start the container with `--gpus all`",
+            _ => "pass the device through to the container, e.g. `--device /dev/dri --device /dev/kfd`",
+        };
+    }
+    match vendor {
+        Vendor::Nvidia => "install the NVIDIA driver, e.g. `sudo ubuntu-drivers install`",
+        Vendor::Amd => "load the amdgpu kernel module or install `amdgpu-dkms` from AMD's repository",
+        Vendor::Intel => "install a kernel with the i915 or xe driver and the `linux-firmware` package",
+        Vendor::Apple | Vendor::Other(_) => "install the vendor's driver",
+    }
+}
+
+/// Check GPU availability and return any warnings for the install summary
+pub fn detect_and_setup() -> Result<Vec<String>> {
+    let mut warnings = Vec::new();
+    let devices = detect_gpu()?;
+    for device in &devices {
+        log_device(device);
+    }
+
+    if devices.iter().any(|device| matches!(device, GpuType::Nvidia(_))) {
+        let stack = CudaStack::detect(Path::new("/"))?;
+        logging::record(
+            Level::Info,
+            "cuda",
+            &[
+                ("driver", version_field(&stack.driver)),
+                ("driver_cuda", version_field(&stack.driver_cuda)),
+                ("toolkit", version_field(&stack.toolkit)),
+                ("cudnn", version_field(&stack.cudnn)),
+            ],
+        );
+        warnings.extend(stack.check().iter().map(CudaIssue::to_string));
+    }
+
+    warnings.extend(runtime_warnings(&devices));
+
+    let environment = Environment::detect();
+    for device in &devices {
+        if let GpuType::DriverMissing(gpu) = device {
+            warnings.push(format!(
+                "GPU present, driver missing: {} at {}; {}",
+                gpu.name(),
+                gpu.slot,
+                driver_hint(gpu.vendor(), &environment)
+            ));
+        }
+    }
+
+    if devices.is_empty() {
+        warnings.push("No GPU acceleration detected, using CPU-only mode".to_string());
+    }
+
+    Ok(warnings)
+}
+
+/// Warnings for AMD and Intel cards whose compute runtime is missing
+///
+/// Integrated graphics are not what ROCm and level-zero are for, so
+/// laptops with UHD/Iris or Radeon APU graphics get no warning.
+fn runtime_warnings(devices: &[GpuType]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let amd: Vec<&AmdGpu> = devices
+        .iter()
+        .filter_map(|device| match device {
+            GpuType::Amd(gpu) if !gpu.integrated => Some(gpu),
+            _ => None,
+        })
+        .collect();
+    if !amd.is_empty() && amd[0].rocm_version.is_none() {
+        warnings.push(format!(
+            "{} AMD GPU(s) found ({}) but ROCm is not installed; \
+             consider installing it for GPU acceleration",
+            amd.len(),
+            amd[0].name
+        ));
+    }
+
+    let intel: Vec<&IntelGpu> = devices
+        .iter()
+        .filter_map(|device| match device {
+            GpuType::Intel(gpu) if !gpu.integrated => Some(gpu),
+            _ => None,
+        })
+        .collect();
+    if !intel.is_empty() && intel[0].level_zero_version.is_none() {
+        warnings.push(format!(
+            "{} Intel GPU(s) found ({}) but the level-zero runtime is not installed; \
+             install it to use the GPU from oneAPI and PyTorch XPU",
+            intel.len(),
+            intel[0].name
+        ));
+    }
+
+    warnings
+}
+
+/// One line description of a device, e.g. "NVIDIA A100 (driver 550.54.14)"
+pub fn describe(device: &GpuType) -> String {
+    let or_none = |value: &Option<String>| value.clone().unwrap_or_else(|| "none".to_string());
+    match device {
+        GpuType::Nvidia(gpu) => format!("{} (driver {})", gpu.name, gpu.driver_version),
+        GpuType::Amd(gpu) => format!("{} (ROCm {})", gpu.name, or_none(&gpu.rocm_version)),
+        GpuType::Intel(gpu) => format!("{} (level-zero {})", gpu.name, or_none(&gpu.level_zero_version)),
+        GpuType::Metal { device_name, architecture } => format!("{} ({})", device_name, architecture),
+        GpuType::DriverMissing(gpu) => format!("{} at {} (no driver)", gpu.name(), gpu.slot),
+    }
+}
+
+fn version_field(version: &Option<cuda::Version>) -> String {
+    version.as_ref().map_or_else(|| "none".to_string(), |v| v.to_string())
+}
+
+/// Record a detected device and its runtime versions in the run log
+fn log_device(device: &GpuType) {
+    let missing = || "none".to_string();
+    let fields = match device {
+        GpuType::Nvidia(gpu) => vec![
+            ("vendor", "nvidia".to_string()),
+            ("name", gpu.name.clone()),
+            ("driver", gpu.driver_version.clone()),
+        ],
+        GpuType::Amd(gpu) => vec![
+            ("vendor", "amd".to_string()),
+            ("name", gpu.name.clone()),
+            ("integrated", gpu.integrated.to_string()),
+            ("gfx_target", gpu.gfx_target.clone().unwrap_or_else(missing)),
+            ("rocm", gpu.rocm_version.clone().unwrap_or_else(missing)),
+        ],
+        GpuType::Intel(gpu) => vec![
+            ("vendor", "intel".to_string()),
+            ("name", gpu.name.clone()),
+            ("integrated", gpu.integrated.to_string()),
+            ("level_zero", gpu.level_zero_version.clone().unwrap_or_else(missing)),
+            ("oneapi", gpu.oneapi_version.clone().unwrap_or_else(missing)),
+        ],
+        GpuType::Metal { device_name, architecture } => vec![
+            ("vendor", "apple".to_string()),
+            ("name", device_name.clone()),
+            ("architecture", architecture.clone()),
+        ],
+        GpuType::DriverMissing(gpu) => vec![
+            ("vendor", format!("{:?}", gpu.vendor()).to_ascii_lowercase()),
+            ("name", gpu.name()),
+            ("slot", gpu.slot.clone()),
+            ("driver", gpu.driver.clone().unwrap_or_else(missing)),
+        ],
+    };
+    logging::record(Level::Info, "gpu", &fields);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::pci::testing::add_device;
+
+    #[test]
+    fn test_missing_drivers() {
+        let root = tempfile::tempdir().unwrap();
+        add_device(root.path(), "0000:01:00.0", "0x030200", "0x10de", "0x2330", Some("nouveau"));
+        add_device(root.path(), "0000:03:00.0", "0x030000", "0x8086", "0x56a0", Some("i915"));
+        add_device(root.path(), "0000:c1:00.0", "0x038000", "0x1002", "0x74a1", None);
+
+        let intel = GpuType::Intel(IntelGpu {
+            name: "Intel Corporation DG2 [Arc A770]".to_string(),
+            device_id: 0x56a0,
+            pci_bus_id: Some("0000:03:00.0".to_string()),
+            integrated: false,
+            level_zero_version: None,
+            oneapi_version: None,
+        });
+
+        let missing = missing_drivers(root.path(), &[intel]);
+        let slots: Vec<&str> = missing.iter().map(|gpu| gpu.slot.as_str()).collect();
+        assert_eq!(slots, vec!["0000:01:00.0", "0000:c1:00.0"]);
+        assert_eq!(missing[1].vendor(), Vendor::Amd);
+    }
+
+    #[test]
+    fn test_runtime_warnings_skip_integrated_graphics() {
+        let root = tempfile::tempdir().unwrap();
+        sysfs::testing::add_card(root.path(), "card0", "0x8086", "0x9a49", "0000:00:02.0");
+        sysfs::testing::add_card(root.path(), "card1", "0x1002", "0x1681", "0000:e5:00.0");
+        let mut devices: Vec<GpuType> = intel::detect(root.path()).into_iter().map(GpuType::Intel).collect();
+        devices.extend(amd::merge(root.path(), &[], None).into_iter().map(GpuType::Amd));
+        assert_eq!(devices.len(), 2);
+        assert!(runtime_warnings(&devices).is_empty());
+
+        sysfs::testing::add_card(root.path(), "card2", "0x8086", "0x56a0", "0000:03:00.0");
+        let devices: Vec<GpuType> = intel::detect(root.path()).into_iter().map(GpuType::Intel).collect();
+        let warnings = runtime_warnings(&devices);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("1 Intel GPU(s) found (Intel Corporation DG2 [Arc A770])"));
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Command;
+use which::which;
+
+/// Fields queried from nvidia-smi, in order
+const QUERY_FIELDS: &[&str] = &[
+    "index",
+    "uuid",
+    "name",
+    "driver_version",
+    "memory.total",
+    "compute_cap",
+    "pci.bus_id",
+];
+
+/// A single NVIDIA device as reported by nvidia-smi
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NvidiaGpu {
+    pub index: u32,
+    pub uuid: String,
+    pub name: String,
+    pub driver_version: String,
+    /// Total memory in bytes, if the driver reports it
+    pub memory_bytes: Option<u64>,
+    /// Compute capability, e.g. "8.0"; not reported by drivers before 510
+    pub compute_capability: Option<String>,
+    pub pci_bus_id: String,
+}
+
+/// Where WSL mounts the Windows driver's tools
+const WSL_SMI: &str = "/usr/lib/wsl/lib/nvidia-smi";
+
+/// Path to nvidia-smi, which WSL keeps off the default PATH
+pub fn smi_path() -> Option<PathBuf> {
+    which("nvidia-smi").ok().or_else(|| {
+        let wsl = PathBuf::from(WSL_SMI);
+        wsl.exists().then_some(wsl)
+    })
+}
+
+/// List NVIDIA devices, or an empty list when nvidia-smi is unavailable
+pub fn detect() -> Result<Vec<NvidiaGpu>> {
+    let smi = match smi_path() {
+        Some(smi) => smi,
+        None => return Ok(Vec::new()),
+    };
+
+    // Older drivers reject compute_cap, so retry without it
+    let without_compute_cap: Vec<&str> = QUERY_FIELDS
+        .iter()
+        .copied()
+        .filter(|field| *field != "compute_cap")
+        .collect();
+
+    for fields in [QUERY_FIELDS.to_vec(), without_compute_cap] {
+        let output = Command::new(&smi)
+            .arg(format!("--query-gpu={}", fields.join(",")))
+            .arg("--format=csv,nounits")
+            .output()?;
+
+        if output.status.success() {
+            return parse_csv(&String::from_utf8_lossy(&output.stdout));
+        }
+    }
+
+    Ok(Vec::new())
+}
+
+/// Parse `nvidia-smi --query-gpu=... --format=csv,nounits` output
+///
+/// Columns are matched by header so the parser works with or without
+/// `compute_cap`. Each row describes exactly one device.
+pub fn parse_csv(output: &str) -> Result<Vec<NvidiaGpu>> {
+    let mut lines = output.lines().filter(|line| !line.trim().is_empty());
+    let header = lines
+        .next()
+        .ok_or_else(|| anyhow!("Empty nvidia-smi output"))?;
+
+    // Headers look like "memory.total [MiB]"; keep the name and unit apart
+    let mut columns = HashMap::new();
+    let mut memory_unit = None;
+    for (position, column) in header.split(',').map(str::trim).enumerate() {
+        let (name, unit) = match column.split_once(" [") {
+            Some((name, unit)) => (name, Some(unit.trim_end_matches(']'))),
+            None => (column, None),
+        };
+        if name == "memory.total" {
+            memory_unit = unit;
+        }
+        columns.insert(name, position);
+    }
+
+    let mut gpus = Vec::new();
+    for line in lines {
+        let values: Vec<&str> = line.split(',').map(str::trim).collect();
+        if values.len() != columns.len() {
+            return Err(anyhow!(
+                "Expected {} fields in nvidia-smi row, got {}: {}",
+                columns.len(),
+                values.len(),
+                line
+            ));
+        }
+
+        let field = |name: &str| {
+            columns
+                .get(name)
+                .map(|&position| values[position])
+                .filter(|value| !value.starts_with('[') && !value.is_empty())
+        };
+        let required = |name: &str| {
+            field(name)
+                .map(str::to_string)
+                .ok_or_else(|| anyhow!("Missing {} in nvidia-smi row: {}", name, line))
+        };
+
+        gpus.push(NvidiaGpu {
+            index: required("index")?.parse()?,
+            uuid: required("uuid")?,
+            name: required("name")?,
+            driver_version: required("driver_version")?,
+            memory_bytes: field("memory.total")
+                .map(|memory| parse_memory(memory, memory_unit))
+                .transpose()?,
+            compute_capability: field("compute_cap").map(str::to_string),
+            pci_bus_id: required("pci.bus_id")?,
+        });
+    }
+
+    Ok(gpus)
+}
+
+/// Convert a memory amount to bytes, e.g. ("81920", "MiB") or "81920 MiB"
+fn parse_memory(value: &str, unit: Option<&str>) -> Result<u64> {
+    let (amount, unit) = match value.split_once(' ') {
+        Some((amount, unit)) => (amount, unit),
+        None => (value, unit.unwrap_or("MiB")),
+    };
+
+    let multiplier = match unit {
+        "B" => 1,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        _ => return Err(anyhow!("Unknown memory unit: {}", unit)),
+    };
+
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| anyhow!("Invalid memory amount: {}", value))?;
+    Ok(amount * multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: u64 = 1 << 20;
+
+    #[test]
+    fn test_parse_single_gpu() {
+        let gpus = parse_csv(include_str!("fixtures/nvidia-smi-single.csv")).unwrap();
+
+        assert_eq!(
+            gpus,
+            vec![NvidiaGpu {
+                index: 0,
+                uuid: "GPU-5c1f3f2e-8d9a-4b71-9e0c-1a2b3c4d5e6f".to_string(),
+                name: "NVIDIA GeForce RTX 4090".to_string(),
+                driver_version: "550.54.14".to_string(),
+                memory_bytes: Some(24564 * MIB),
+                compute_capability: Some("8.9".to_string()),
+                pci_bus_id: "00000000:01:00.0".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_multi_gpu_rows_stay_separate() {
+        let gpus = parse_csv(include_str!("fixtures/nvidia-smi-4x-a100.csv")).unwrap();
+        assert_eq!(gpus.len(), 4);
+        for (i, gpu) in gpus.iter().enumerate() {
+            assert_eq!(gpu.index, i as u32);
+            assert_eq!(gpu.name, "NVIDIA A100-SXM4-80GB");
+            assert_eq!(gpu.memory_bytes, Some(81920 * MIB));
+        }
+        assert_eq!(gpus[3].pci_bus_id, "00000000:4E:00.0");
+
+        let gpus = parse_csv(include_str!("fixtures/nvidia-smi-8x-h100.csv")).unwrap();
+        assert_eq!(gpus.len(), 8);
+        assert_eq!(gpus[7].uuid, "GPU-a7b8c9d0-a1b2-7d8e-9f0a-1b2c3d4e5f67");
+        assert_eq!(gpus[7].compute_capability.as_deref(), Some("9.0"));
+    }
+
+    #[test]
+    fn test_parse_legacy_driver_without_compute_cap() {
+        let gpus = parse_csv(include_str!("fixtures/nvidia-smi-legacy.csv")).unwrap();
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].compute_capability, None);
+        assert_eq!(gpus[0].memory_bytes, Some(16160 * MIB));
+        assert_eq!(gpus[1].memory_bytes, None);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_rows() {
+        let output = "index, uuid, name\n0, GPU-1, Tesla, extra\n";
+        assert!(parse_csv(output).is_err());
+        assert!(parse_csv("").is_err());
+    }
+}
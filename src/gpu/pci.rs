@@ -0,0 +1,216 @@
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::sysfs::{self, VENDOR_AMD, VENDOR_INTEL};
+use super::Vendor;
+
+/// PCI base class for display controllers
+const CLASS_DISPLAY: u32 = 0x03;
+
+/// AMD and Intel discrete cards from the bundled ID list
+///
+/// Both vendors also make integrated graphics (Radeon APUs, UHD/Iris),
+/// which sysfs does not tell apart from cards. A model missing here
+/// counts as integrated until it is added.
+const DISCRETE: &[(u16, &[u16])] = &[
+    (VENDOR_AMD, &[0x738c, 0x73bf, 0x740c, 0x740f, 0x744c, 0x74a1]),
+    (VENDOR_INTEL, &[0x0bd5, 0x56a0, 0x56a1, 0x56a5, 0x56c0]),
+];
+
+/// Vendor name and device names by id, from the bundled `pci.ids` subset
+type PciIds = HashMap<u16, (&'static str, HashMap<u16, &'static str>)>;
+
+static PCI_IDS: Lazy<PciIds> = Lazy::new(|| parse_ids(include_str!("pci.ids")));
+
+/// A display controller found on the PCI bus
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PciGpu {
+    /// PCI address, e.g. "0000:01:00.0"
+    pub slot: String,
+    pub vendor_id: u16,
+    pub device_id: u16,
+    /// Kernel driver bound to the device, if any
+    pub driver: Option<String>,
+}
+
+impl PciGpu {
+    pub fn vendor(&self) -> Vendor {
+        Vendor::from_id(self.vendor_id)
+    }
+
+    /// Vendor and model from the bundled ID list, e.g. "NVIDIA Corporation AD102 [GeForce RTX 4090]"
+    pub fn name(&self) -> String {
+        model_name(self.vendor_id, self.device_id)
+    }
+}
+
+/// List display controllers below `root`, which is `/` outside of tests
+///
+/// Unlike the vendor tools this works before any driver is installed.
+pub fn scan(root: &Path) -> Vec<PciGpu> {
+    let devices = root.join("sys/bus/pci/devices");
+    let entries = match fs::read_dir(&devices) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut gpus: Vec<PciGpu> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let dir = entry.path();
+            let class = sysfs::read_attr(&dir.join("class"))?;
+            let class = u32::from_str_radix(class.trim_start_matches("0x"), 16).ok()?;
+            if class >> 16 != CLASS_DISPLAY {
+                return None;
+            }
+
+            let driver = fs::read_link(dir.join("driver"))
+                .ok()
+                .and_then(|target| target.file_name().map(|name| name.to_string_lossy().into_owned()))
+                .or_else(|| sysfs::read_uevent(&dir, "DRIVER"));
+
+            Some(PciGpu {
+                slot: entry.file_name().to_string_lossy().into_owned(),
+                vendor_id: sysfs::read_hex(&dir.join("vendor"))?,
+                device_id: sysfs::read_hex(&dir.join("device"))?,
+                driver,
+            })
+        })
+        .collect();
+    gpus.sort_by(|a, b| a.slot.cmp(&b.slot));
+    gpus
+}
+
+/// Human readable name for a PCI id pair, falling back to the raw ids
+pub fn model_name(vendor_id: u16, device_id: u16) -> String {
+    let ids = format!("[{:04x}:{:04x}]", vendor_id, device_id);
+    match PCI_IDS.get(&vendor_id) {
+        Some((vendor, models)) => match models.get(&device_id) {
+            Some(model) => format!("{} {}", vendor, model),
+            None => format!("{} device {}", vendor, ids),
+        },
+        None => format!("Unknown GPU {}", ids),
+    }
+}
+
+/// Whether a device is a discrete card rather than integrated graphics
+///
+/// Only AMD and Intel make both; other vendors' devices are all cards.
+pub fn is_discrete(vendor_id: u16, device_id: u16) -> bool {
+    DISCRETE
+        .iter()
+        .find(|(vendor, _)| *vendor == vendor_id)
+        .is_none_or(|(_, devices)| devices.contains(&device_id))
+}
+
+/// Whether two PCI addresses name the same device
+///
+/// nvidia-smi pads the domain to eight digits, sysfs uses four.
+pub fn same_slot(a: &str, b: &str) -> bool {
+    let strip = |slot: &str| {
+        slot.split_once(':')
+            .map_or(slot, |(_, rest)| rest)
+            .to_ascii_lowercase()
+    };
+    strip(a) == strip(b)
+}
+
+/// Parse the `pci.ids` format: vendors at column 0, devices indented one tab
+fn parse_ids(contents: &'static str) -> PciIds {
+    let mut ids = PciIds::new();
+    let mut vendor = None;
+
+    for line in contents.lines() {
+        if line.starts_with('#') || line.trim().is_empty() || line.starts_with("\t\t") {
+            continue;
+        }
+
+        let (id, name) = match line.trim().split_once("  ") {
+            Some((id, name)) => (id, name.trim()),
+            None => continue,
+        };
+        let id = match u16::from_str_radix(id, 16) {
+            Ok(id) => id,
+            Err(_) => continue,
+        };
+
+        if line.starts_with('\t') {
+            if let Some(entry) = vendor.and_then(|vendor| ids.get_mut(&vendor)) {
+                entry.1.insert(id, name);
+            }
+        } else {
+            ids.insert(id, (name, HashMap::new()));
+            vendor = Some(id);
+        }
+    }
+
+    ids
+}
+
+#[cfg(test)]
+pub mod testing {
+    use std::fs;
+    use std::path::Path;
+
+    /// Create a fake `/sys/bus/pci/devices/<slot>` entry below `root`
+    pub fn add_device(root: &Path, slot: &str, class: &str, vendor: &str, device: &str, driver: Option<&str>) {
+        let dir = root.join("sys/bus/pci/devices").join(slot);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("class"), format!("{}\n", class)).unwrap();
+        fs::write(dir.join("vendor"), format!("{}\n", vendor)).unwrap();
+        fs::write(dir.join("device"), format!("{}\n", device)).unwrap();
+        let uevent = match driver {
+            Some(driver) => format!("DRIVER={}\nPCI_SLOT_NAME={}\n", driver, slot),
+            None => format!("PCI_SLOT_NAME={}\n", slot),
+        };
+        fs::write(dir.join("uevent"), uevent).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::add_device;
+    use super::*;
+
+    #[test]
+    fn test_scan_finds_display_controllers() {
+        let root = tempfile::tempdir().unwrap();
+        add_device(root.path(), "0000:01:00.0", "0x030200", "0x10de", "0x20b2", None);
+        add_device(root.path(), "0000:00:02.0", "0x030000", "0x8086", "0x4680", Some("i915"));
+        // A network card is not a GPU
+        add_device(root.path(), "0000:02:00.0", "0x020000", "0x8086", "0x1533", Some("igb"));
+
+        let gpus = scan(root.path());
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].slot, "0000:00:02.0");
+        assert_eq!(gpus[0].driver.as_deref(), Some("i915"));
+        assert_eq!(gpus[1].vendor(), Vendor::Nvidia);
+        assert_eq!(gpus[1].driver, None);
+        assert_eq!(gpus[1].name(), "NVIDIA Corporation GA100 [A100 SXM4 80GB]");
+    }
+
+    #[test]
+    fn test_model_name_fallbacks() {
+        assert_eq!(model_name(0x1002, 0x740c), "Advanced Micro Devices, Inc. [AMD/ATI] Aldebaran/MI200 [Instinct MI250X/MI250]");
+        assert_eq!(model_name(0x10de, 0xffff), "NVIDIA Corporation device [10de:ffff]");
+        assert_eq!(model_name(0x1234, 0x1111), "Unknown GPU [1234:1111]");
+    }
+
+    #[test]
+    fn test_is_discrete() {
+        assert!(is_discrete(0x8086, 0x56a0));
+        assert!(!is_discrete(0x8086, 0x9a49));
+        assert!(is_discrete(0x1002, 0x74a1));
+        assert!(!is_discrete(0x1002, 0x1681));
+        assert!(is_discrete(0x10de, 0x2684));
+    }
+
+    #[test]
+    fn test_same_slot_ignores_domain_width() {
+        assert!(same_slot("00000000:4E:00.0", "0000:4e:00.0"));
+        assert!(!same_slot("0000:01:00.0", "0000:02:00.0"));
+    }
+}
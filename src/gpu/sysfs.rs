@@ -0,0 +1,114 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const VENDOR_AMD: u16 = 0x1002;
+pub const VENDOR_INTEL: u16 = 0x8086;
+pub const VENDOR_NVIDIA: u16 = 0x10de;
+
+/// A DRM card as exposed under `/sys/class/drm/cardN`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrmCard {
+    pub vendor_id: u16,
+    pub device_id: u16,
+    /// PCI address, e.g. "0000:03:00.0"
+    pub pci_slot: Option<String>,
+    /// The card's `device` directory, for driver specific attributes
+    pub device_dir: PathBuf,
+}
+
+/// List DRM cards below `root`, which is `/` outside of tests
+pub fn drm_cards(root: &Path) -> Vec<DrmCard> {
+    let drm = root.join("sys/class/drm");
+    let entries = match fs::read_dir(&drm) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        // Skip connectors like "card0-DP-1"
+        .filter(|name| {
+            name.strip_prefix("card")
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        })
+        .collect();
+    names.sort_by_key(|name| name[4..].parse::<u32>().unwrap_or(u32::MAX));
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let device_dir = drm.join(&name).join("device");
+            Some(DrmCard {
+                vendor_id: read_hex(&device_dir.join("vendor"))?,
+                device_id: read_hex(&device_dir.join("device"))?,
+                pci_slot: read_uevent(&device_dir, "PCI_SLOT_NAME"),
+                device_dir,
+            })
+        })
+        .collect()
+}
+
+/// Read a sysfs id file like "0x1002"
+pub fn read_hex(path: &Path) -> Option<u16> {
+    let contents = fs::read_to_string(path).ok()?;
+    u16::from_str_radix(contents.trim().trim_start_matches("0x"), 16).ok()
+}
+
+/// Read a trimmed single value sysfs attribute
+pub fn read_attr(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|contents| contents.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Look up `KEY=value` in a device's `uevent` file
+pub fn read_uevent(device_dir: &Path, key: &str) -> Option<String> {
+    let contents = fs::read_to_string(device_dir.join("uevent")).ok()?;
+    contents.lines().find_map(|line| {
+        line.strip_prefix(key)
+            .and_then(|rest| rest.strip_prefix('='))
+            .map(str::to_string)
+    })
+}
+
+#[cfg(test)]
+pub mod testing {
+    use std::fs;
+    use std::path::Path;
+
+    /// Create a fake `/sys/class/drm/<card>` entry below `root`
+    pub fn add_card(root: &Path, card: &str, vendor: &str, device: &str, slot: &str) -> std::path::PathBuf {
+        let device_dir = root.join("sys/class/drm").join(card).join("device");
+        fs::create_dir_all(&device_dir).unwrap();
+        fs::write(device_dir.join("vendor"), format!("{}\n", vendor)).unwrap();
+        fs::write(device_dir.join("device"), format!("{}\n", device)).unwrap();
+        fs::write(
+            device_dir.join("uevent"),
+            format!("DRIVER=test\nPCI_SLOT_NAME={}\n", slot),
+        )
+        .unwrap();
+        device_dir
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drm_cards() {
+        let root = tempfile::tempdir().unwrap();
+        testing::add_card(root.path(), "card1", "0x8086", "0x56a0", "0000:03:00.0");
+        testing::add_card(root.path(), "card0", "0x1002", "0x740f", "0000:c1:00.0");
+        fs::create_dir_all(root.path().join("sys/class/drm/card0-DP-1")).unwrap();
+
+        let cards = drm_cards(root.path());
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].vendor_id, VENDOR_AMD);
+        assert_eq!(cards[0].device_id, 0x740f);
+        assert_eq!(cards[0].pci_slot.as_deref(), Some("0000:c1:00.0"));
+        assert_eq!(cards[1].vendor_id, VENDOR_INTEL);
+    }
+}
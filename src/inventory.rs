@@ -0,0 +1,216 @@
+use anyhow::{anyhow, Result};
+use dirs::home_dir;
+use serde::Serialize;
+use std::ffi::CString;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+use crate::environment::{Environment, Virtualization};
+use crate::gpu::{self, GpuType};
+use crate::package::{manager::PackageManager, sys};
+
+/// Hardware and OS facts for fleet tooling and bug reports
+#[derive(Debug, Serialize)]
+pub struct Inventory {
+    pub setcyrup_version: &'static str,
+    pub os: String,
+    pub arch: String,
+    pub kernel: Option<String>,
+    pub environment: Environment,
+    pub cpu: Cpu,
+    pub memory_bytes: Option<u64>,
+    pub disk: Option<Disk>,
+    pub package_manager: Option<String>,
+    pub gpus: Vec<GpuType>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Cpu {
+    pub model: Option<String>,
+    /// Logical cores, including hyperthreads
+    pub cores: usize,
+}
+
+/// Space on the filesystem holding the home directory
+#[derive(Debug, Serialize)]
+pub struct Disk {
+    pub path: PathBuf,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+}
+
+impl Inventory {
+    /// Gather the inventory for this machine
+    pub fn collect() -> Result<Self> {
+        let root = Path::new("/");
+        let (os, arch) = sys::detect_platform()?;
+        let cpuinfo = fs::read_to_string(root.join("proc/cpuinfo")).unwrap_or_default();
+
+        let mut cpu = parse_cpuinfo(&cpuinfo);
+        if cpu.cores == 0 {
+            cpu.cores = std::thread::available_parallelism().map_or(0, usize::from);
+        }
+
+        let disk = match home_dir() {
+            Some(home) => Some(disk_usage(&home)?),
+            None => None,
+        };
+
+        Ok(Self {
+            setcyrup_version: env!("CARGO_PKG_VERSION"),
+            os,
+            arch,
+            kernel: fs::read_to_string(root.join("proc/sys/kernel/osrelease"))
+                .ok()
+                .map(|release| release.trim().to_string()),
+            environment: Environment::detect(),
+            cpu,
+            memory_bytes: fs::read_to_string(root.join("proc/meminfo"))
+                .ok()
+                .and_then(|meminfo| parse_meminfo(&meminfo)),
+            disk,
+            package_manager: PackageManager::detect().ok().map(|pm| pm.to_string()),
+            gpus: gpu::detect_gpu()?,
+        })
+    }
+}
+
+/// Print the inventory, as JSON or as a short human readable list
+pub fn show(json: bool) -> Result<()> {
+    let inventory = Inventory::collect()?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&inventory)?);
+        return Ok(());
+    }
+
+    let gib = |bytes: u64| format!("{:.1} GiB", bytes as f64 / (1u64 << 30) as f64);
+    let unknown = || "unknown".to_string();
+
+    println!("Platform:        {}/{}", inventory.os, inventory.arch);
+    println!("Kernel:          {}", inventory.kernel.clone().unwrap_or_else(unknown));
+    println!("Environment:     {}", describe_environment(&inventory.environment));
+    println!(
+        "CPU:             {} ({} cores)",
+        inventory.cpu.model.clone().unwrap_or_else(unknown),
+        inventory.cpu.cores
+    );
+    println!("Memory:          {}", inventory.memory_bytes.map_or_else(unknown, gib));
+    if let Some(disk) = &inventory.disk {
+        println!(
+            "Disk:            {} free of {} ({})",
+            gib(disk.free_bytes),
+            gib(disk.total_bytes),
+            disk.path.display()
+        );
+    }
+    println!(
+        "Package manager: {}",
+        inventory.package_manager.clone().unwrap_or_else(|| "none".to_string())
+    );
+
+    if inventory.gpus.is_empty() {
+        println!("GPUs:            none");
+    }
+    for device in &inventory.gpus {
+        println!("GPU:             {}", gpu::describe(device));
+    }
+    Ok(())
+}
+
+fn describe_environment(environment: &Environment) -> String {
+    let mut description = match &environment.virtualization {
+        Virtualization::BareMetal => "bare metal".to_string(),
+        Virtualization::Container { runtime } => format!("container ({})", runtime),
+        Virtualization::Vm { hypervisor: Some(hypervisor) } => format!("virtual machine ({})", hypervisor),
+        Virtualization::Vm { hypervisor: None } => "virtual machine".to_string(),
+        Virtualization::Wsl => "WSL".to_string(),
+    };
+    if environment.devcontainer {
+        description.push_str(", dev container");
+    }
+    if let Some(ci) = &environment.ci {
+        description.push_str(&format!(", CI ({})", ci));
+    }
+    if environment.is_root {
+        description.push_str(", root");
+    }
+    description
+}
+
+/// CPU model and logical core count from `/proc/cpuinfo`
+fn parse_cpuinfo(contents: &str) -> Cpu {
+    let mut cpu = Cpu::default();
+    for line in contents.lines() {
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue,
+        };
+        match key {
+            "processor" => cpu.cores += 1,
+            // "Model" is what ARM boards report
+            "model name" | "Model" if cpu.model.is_none() => cpu.model = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    cpu
+}
+
+/// Total RAM in bytes from `/proc/meminfo`
+fn parse_meminfo(contents: &str) -> Option<u64> {
+    let line = contents.lines().find(|line| line.starts_with("MemTotal:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Total and available space on the filesystem holding `path`
+pub fn disk_usage(path: &Path) -> Result<Disk> {
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path is NUL terminated and stat is a valid out pointer
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(anyhow!(
+            "statvfs failed for {}: {}",
+            path.display(),
+            std::io::Error::last_os_error()
+        ));
+    }
+
+    let block = stat.f_frsize as u64;
+    Ok(Disk {
+        path: path.to_path_buf(),
+        total_bytes: stat.f_blocks as u64 * block,
+        free_bytes: stat.f_bavail as u64 * block,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_files() {
+        let cpuinfo = "processor\t: 0\nmodel name\t: AMD EPYC 7763 64-Core Processor\nflags\t\t: fpu vme\n\n\
+                       processor\t: 1\nmodel name\t: AMD EPYC 7763 64-Core Processor\n";
+        assert_eq!(
+            parse_cpuinfo(cpuinfo),
+            Cpu {
+                model: Some("AMD EPYC 7763 64-Core Processor".to_string()),
+                cores: 2,
+            }
+        );
+
+        let meminfo = "MemTotal:       65849912 kB\nMemFree:        1234 kB\n";
+        assert_eq!(parse_meminfo(meminfo), Some(65849912 * 1024));
+    }
+
+    #[test]
+    fn test_disk_usage_and_json_shape() {
+        let dir = tempfile::tempdir().unwrap();
+        let disk = disk_usage(dir.path()).unwrap();
+        assert!(disk.total_bytes >= disk.free_bytes);
+
+        let json = serde_json::to_value(Virtualization::Vm { hypervisor: Some("kvm".to_string()) }).unwrap();
+        assert_eq!(json, serde_json::json!({"kind": "vm", "hypervisor": "kvm"}));
+    }
+}
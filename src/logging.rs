@@ -0,0 +1,269 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use dirs::home_dir;
+use once_cell::sync::Lazy;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Severity of a log record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Error => write!(f, "error"),
+            Level::Warn => write!(f, "warn"),
+            Level::Info => write!(f, "info"),
+            Level::Debug => write!(f, "debug"),
+            Level::Trace => write!(f, "trace"),
+        }
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(Level::Error),
+            "warn" | "warning" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!("Unknown log level: {}", s)),
+        }
+    }
+}
+
+struct Logger {
+    file: File,
+    level: Level,
+}
+
+static LOGGER: Lazy<Mutex<Option<Logger>>> = Lazy::new(|| Mutex::new(None));
+
+/// Directory holding one log file per run
+pub fn log_dir() -> Result<PathBuf> {
+    let home = home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+    Ok(home.join(".local/state/cyrup/logs"))
+}
+
+/// Start logging this run to a new timestamped file, returning its path
+pub fn init(level: Level) -> Result<PathBuf> {
+    let dir = log_dir()?;
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+
+    let path = dir.join(format!("{}.log", Utc::now().format("%Y%m%dT%H%M%S%.3fZ")));
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open log file {}", path.display()))?;
+
+    *LOGGER.lock().unwrap_or_else(|e| e.into_inner()) = Some(Logger { file, level });
+
+    log_environment();
+    Ok(path)
+}
+
+/// Write a structured record if logging is enabled at `level`
+pub fn record(level: Level, event: &str, fields: &[(&str, String)]) {
+    let mut guard = LOGGER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(logger) = guard.as_mut() {
+        if level <= logger.level {
+            let line = format_record(&Utc::now().to_rfc3339(), level, event, fields);
+            // Logging must never abort an installation
+            let _ = writeln!(logger.file, "{}", line);
+        }
+    }
+}
+
+pub fn error(message: impl fmt::Display) {
+    record(Level::Error, "message", &[("msg", message.to_string())]);
+}
+
+pub fn warn(message: impl fmt::Display) {
+    record(Level::Warn, "message", &[("msg", message.to_string())]);
+}
+
+pub fn info(message: impl fmt::Display) {
+    record(Level::Info, "message", &[("msg", message.to_string())]);
+}
+
+/// Record a finished command with its exit code, duration and captured output
+pub fn command(
+    cmd: &str,
+    args: &[&str],
+    exit_code: Option<i32>,
+    duration: Duration,
+    stdout: &str,
+    stderr: &str,
+) {
+    let success = exit_code == Some(0);
+    let mut fields = vec![
+        ("cmd", cmd.to_string()),
+        ("args", format!("{:?}", args)),
+        (
+            "exit_code",
+            exit_code.map_or_else(|| "signal".to_string(), |code| code.to_string()),
+        ),
+        ("duration_ms", duration.as_millis().to_string()),
+    ];
+    if !stdout.is_empty() {
+        fields.push(("stdout", stdout.to_string()));
+    }
+    if !stderr.is_empty() {
+        fields.push(("stderr", stderr.to_string()));
+    }
+
+    let level = if success { Level::Info } else { Level::Warn };
+    record(level, "command", &fields);
+}
+
+/// Record a retry of a failed operation
+pub fn retry(attempt: u32, max_attempts: u32, delay: Duration, error: &anyhow::Error) {
+    record(
+        Level::Warn,
+        "retry",
+        &[
+            ("attempt", attempt.to_string()),
+            ("max_attempts", max_attempts.to_string()),
+            ("delay_ms", delay.as_millis().to_string()),
+            ("error", format!("{:#}", error)),
+        ],
+    );
+}
+
+/// Record facts about the machine this run is happening on
+fn log_environment() {
+    let var = |name: &str| std::env::var(name).unwrap_or_default();
+    let package_manager = crate::package::manager::PackageManager::detect()
+        .map(|pm| pm.to_string())
+        .unwrap_or_else(|e| format!("none ({})", e));
+
+    record(
+        Level::Info,
+        "environment",
+        &[
+            ("version", env!("CARGO_PKG_VERSION").to_string()),
+            ("os", std::env::consts::OS.to_string()),
+            ("arch", std::env::consts::ARCH.to_string()),
+            ("user", var("USER")),
+            ("shell", var("SHELL")),
+            ("home", var("HOME")),
+            ("path", var("PATH")),
+            ("package_manager", package_manager),
+        ],
+    );
+}
+
+/// Format a record as a single logfmt line
+fn format_record(timestamp: &str, level: Level, event: &str, fields: &[(&str, String)]) -> String {
+    let mut line = format!("ts={} level={} event={}", timestamp, level, event);
+    for (key, value) in fields {
+        line.push(' ');
+        line.push_str(key);
+        line.push('=');
+        line.push_str(&quote(value));
+    }
+    line
+}
+
+/// Quote a value only when it needs it, escaping newlines and quotes
+fn quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| !c.is_whitespace() && c != '"' && c != '=' && !c.is_control());
+    if plain {
+        value.to_string()
+    } else {
+        format!("{:?}", value)
+    }
+}
+
+/// Find the most recent log file in `dir`
+fn latest_log_in(dir: &Path) -> Result<Option<PathBuf>> {
+    if !dir.exists() {
+        return Ok(None);
+    }
+
+    // File names are UTC timestamps, so the newest sorts last
+    let latest = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+        .max();
+    Ok(latest)
+}
+
+/// Print the log of the most recent run
+pub fn show_latest() -> Result<()> {
+    let dir = log_dir()?;
+    let path = latest_log_in(&dir)?
+        .ok_or_else(|| anyhow!("No logs found in {}", dir.display()))?;
+
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    println!("# {}", path.display());
+    print!("{}", contents);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_parsing_and_order() {
+        assert_eq!("DEBUG".parse::<Level>().unwrap(), Level::Debug);
+        assert_eq!("warning".parse::<Level>().unwrap(), Level::Warn);
+        assert!("loud".parse::<Level>().is_err());
+        assert!(Level::Error < Level::Info);
+    }
+
+    #[test]
+    fn test_format_record_quotes_values() {
+        let line = format_record(
+            "2026-01-01T00:00:00+00:00",
+            Level::Info,
+            "command",
+            &[
+                ("cmd", "apt-get".to_string()),
+                ("stdout", "line one\nsaid \"hi\"".to_string()),
+            ],
+        );
+        assert_eq!(
+            line,
+            "ts=2026-01-01T00:00:00+00:00 level=info event=command cmd=apt-get \
+             stdout=\"line one\\nsaid \\\"hi\\\"\""
+        );
+    }
+
+    #[test]
+    fn test_latest_log_in() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(latest_log_in(dir.path()).unwrap(), None);
+
+        fs::write(dir.path().join("20260101T000000.000Z.log"), "").unwrap();
+        fs::write(dir.path().join("20260102T000000.000Z.log"), "").unwrap();
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        assert_eq!(
+            latest_log_in(dir.path()).unwrap(),
+            Some(dir.path().join("20260102T000000.000Z.log"))
+        );
+    }
+}
@@ -1,40 +1,207 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
 
+use cli::{Cli, Command, ExportArgs, ExportFormat, InstallArgs, UninstallArgs};
+
+mod checkpoint;
+mod cli;
+mod devcontainer;
+mod dockerfile;
+mod doctor;
+mod environment;
+mod executor;
+mod logging;
 mod package;
+mod preflight;
 mod recipes;
 mod setcyrup;
 mod shell;
 mod gpu;
+mod inventory;
 mod ui;
 mod menu;
 mod system;
+mod report;
+mod steps;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Show module selection menu and get installation plan
-    let install_plan = menu::show_module_menu().await?;
-    
-    // Show confirmation with plan details
-    if !menu::confirm_installation(&install_plan)? {
-        println!("Installation cancelled. Exiting...");
+    let cli = Cli::parse();
+
+    let command = cli
+        .command
+        .clone()
+        .unwrap_or_else(|| Command::Install(cli.install.clone()));
+
+    match command {
+        Command::Install(args) => install(&cli, &args).await,
+        Command::Logs => logging::show_latest(),
+        Command::Inventory(args) => inventory::show(args.json),
+        Command::Doctor(args) => doctor::run(args.fix),
+        Command::Uninstall(args) => uninstall(&cli, &args),
+        Command::Export(args) => export(&args),
+    }
+}
+
+fn uninstall(cli: &Cli, args: &UninstallArgs) -> Result<()> {
+    logging::init(cli.log_level)?;
+    let checkpoint_path = checkpoint::CheckpointStore::default_path()?;
+    let mut store = checkpoint::CheckpointStore::load(&checkpoint_path)?;
+    let saved = store
+        .plan()
+        .cloned()
+        .ok_or_else(|| anyhow!("Nothing to uninstall, no previous installation was found"))?;
+
+    // System packages stay; other software may depend on them
+    let plan = menu::InstallPlan::from_saved(&saved, package::Privilege::UserOnly)?;
+
+    println!("Uninstalling {}", saved.components.join(", "));
+    if !args.yes && !inquire::Confirm::new("Remove these components?").with_default(false).prompt()? {
+        println!("Uninstall cancelled.");
+        return Ok(());
+    }
+    setcyrup::run_uninstaller(&plan, &mut store)
+}
+
+fn export(args: &ExportArgs) -> Result<()> {
+    let checkpoint_path = checkpoint::CheckpointStore::default_path()?;
+    let store = checkpoint::CheckpointStore::load(&checkpoint_path)?;
+    let saved = store
+        .plan()
+        .ok_or_else(|| anyhow!("Nothing to export, run setcyrup to choose components first"))?;
+    let plan = menu::InstallPlan::from_saved(saved, package::Privilege::Root)?;
+
+    let recipe = match args.format {
+        ExportFormat::Dockerfile => dockerfile::render(&plan),
+    };
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, recipe).with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("✅ Wrote {}", path.display());
+        }
+        None => print!("{}", recipe),
+    }
+    Ok(())
+}
+
+fn write_devcontainer(plan: &menu::InstallPlan) -> Result<()> {
+    let project = std::env::current_dir()?;
+    if project.join(".devcontainer/devcontainer.json").exists()
+        && !inquire::Confirm::new("Replace the existing .devcontainer configuration?")
+            .with_default(false)
+            .prompt()?
+    {
+        println!("Kept the existing .devcontainer configuration.");
         return Ok(());
     }
 
+    let (dir, backups) = devcontainer::write(plan, &project)?;
+    for backup in backups {
+        println!("Moved the existing file to {}", backup.display());
+    }
+    println!("✅ Wrote the Dev Container configuration to {}", dir.display());
+    println!("Open this folder in VS Code and run \"Dev Containers: Reopen in Container\" to build it.");
+    Ok(())
+}
+
+async fn install(cli: &Cli, args: &InstallArgs) -> Result<()> {
+    let log_path = logging::init(cli.log_level)?;
+    let checkpoint_path = checkpoint::CheckpointStore::default_path()?;
+    let privilege = package::Privilege::detect(args.user_only);
+    if privilege == package::Privilege::UserOnly && !args.user_only {
+        println!("Neither sudo nor doas found, installing in user-only mode.");
+    }
+
+    // Resume the saved selection, or ask for a new one
+    let saved = if args.resume {
+        let store = checkpoint::CheckpointStore::load(&checkpoint_path)?;
+        store
+            .plan()
+            .map(|saved| menu::InstallPlan::from_saved(saved, privilege))
+            .transpose()?
+            .map(|plan| (plan, store))
+    } else {
+        None
+    };
+
+    let (install_plan, checkpoints) = match saved {
+        Some(saved) => {
+            println!("Resuming previous installation...");
+            saved
+        }
+        None => {
+            if args.resume {
+                println!("No previous installation to resume, starting a new one.");
+            }
+
+            // Show module selection menu and get installation plan
+            let rust = args.rust_spec()?;
+            let mut install_plan = menu::show_module_menu(privilege).await?;
+            install_plan.rust = rust;
+
+            // Show confirmation with plan details
+            if !menu::confirm_installation(&mut install_plan)? {
+                println!("Installation cancelled. Exiting...");
+                return Ok(());
+            }
+            // The toolchain may have changed on the confirmation screen
+            install_plan.rust.validate()?;
+
+            // Dev Containers are built by the editor, nothing runs on the host
+            if install_plan.target == Some(menu::InstallTarget::DevContainer) {
+                return write_devcontainer(&install_plan);
+            }
+
+            let store =
+                checkpoint::CheckpointStore::fresh(&checkpoint_path, install_plan.to_saved())?;
+            (install_plan, store)
+        }
+    };
+
+    // Catch problems before the UI takes over the terminal; this also
+    // caches sudo or doas credentials
+    if args.skip_preflight {
+        if preflight::needs_root(&install_plan) {
+            install_plan.privilege.ensure_access()?;
+        }
+    } else {
+        let checks = preflight::run(&install_plan);
+        preflight::print("Preflight checks", &checks);
+        let failed = checks.iter().filter(|check| check.is_failed()).count();
+        if failed > 0 {
+            logging::error(format!("{} preflight check(s) failed", failed));
+            return Err(anyhow!(
+                "{} preflight check(s) failed; fix the problems above or pass --skip-preflight",
+                failed
+            ));
+        }
+    }
+
     // Create and initialize the UI
     let mut installer = ui::Installer::new()?;
     installer.start()?;
 
     // Run the installer
-    let result = setcyrup::run_installer(&mut installer).await;
+    let mut report = report::InstallReport::new();
+    report.log_path = Some(log_path);
+    let result = setcyrup::run_installer(
+        &mut installer,
+        &install_plan,
+        &mut report,
+        checkpoints,
+        args.jobs,
+    )
+    .await;
+
+    // Keep the summary on screen until the user has seen it
+    installer.show_summary(&report)?;
+    drop(installer);
 
-    // Show final status
     match result {
-        Ok(_) => {
-            installer.update_status("✨ Installation complete! Please restart your terminal.")?;
-            std::process::exit(0);
-        }
+        Ok(_) => Ok(()),
         Err(e) => {
-            installer.update_status(format!("❌ Installation failed: {}", e))?;
+            logging::error(format!("Installation failed: {:#}", e));
+            eprintln!("❌ Installation failed: {:#}", e);
             std::process::exit(1);
         }
     }
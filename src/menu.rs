@@ -1,11 +1,18 @@
 use anyhow::{anyhow, Result};
-use inquire::{MultiSelect, Select};
+use inquire::validator::Validation;
+use inquire::{MultiSelect, Select, Text};
 use std::fmt;
 use crossterm::{
     style::{Color, Print, ResetColor, SetForegroundColor},
     ExecutableCommand,
 };
 
+use crate::checkpoint::SavedPlan;
+use crate::environment::Environment;
+use crate::gpu::{Accelerator, Hardware};
+use crate::package::Privilege;
+use crate::steps::rust::{Channel, RustSpec};
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum InstallTarget {
     Host,
@@ -21,14 +28,76 @@ impl fmt::Display for InstallTarget {
     }
 }
 
+/// Language toolchains a component sets up beyond its system packages
+#[derive(Debug, Clone, PartialEq)]
+pub enum Toolchain {
+    Rust,
+    Python,
+    /// Node.js LTS through fnm, with corepack for pnpm and yarn
+    Node,
+    /// Go from the official tarball, with `go install` tools
+    Go,
+    /// Prebuilt tools from GitHub releases, see `catalog::get_release_binaries`
+    Releases,
+    /// Python ML libraries in a locked virtualenv, with a Jupyter kernel
+    Ml,
+}
+
+/// Long-running services a component needs
+///
+/// Dev Containers run them next to the workspace container; host
+/// installs only get the component's client packages.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Service {
+    Postgres,
+}
+
+impl Service {
+    pub fn image(&self) -> &'static str {
+        match self {
+            Service::Postgres => "postgres:16",
+        }
+    }
+
+    pub fn port(&self) -> u16 {
+        match self {
+            Service::Postgres => 5432,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct InstallModule {
     pub name: String,
     pub description: String,
+    /// System packages needed whatever the accelerator
     pub dependencies: Vec<String>,
+    /// Extra system packages per accelerator, for components that use the GPU
+    pub variants: Vec<(Accelerator, Vec<String>)>,
+    pub toolchains: Vec<Toolchain>,
+    pub services: Vec<Service>,
+    /// Rough disk space needed, in MiB, for preflight checks
+    pub disk_mb: u64,
     pub commands: Vec<String>,
 }
 
+impl InstallModule {
+    /// System packages to install when building for `accelerator`
+    pub fn dependencies_for(&self, accelerator: Accelerator) -> Vec<String> {
+        let variant = self
+            .variants
+            .iter()
+            .find(|(candidate, _)| *candidate == accelerator)
+            .map(|(_, dependencies)| dependencies.as_slice())
+            .unwrap_or_default();
+        self.dependencies.iter().chain(variant).cloned().collect()
+    }
+
+    pub fn has_variants(&self) -> bool {
+        !self.variants.is_empty()
+    }
+}
+
 impl fmt::Display for InstallModule {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.name)
@@ -38,6 +107,96 @@ impl fmt::Display for InstallModule {
 pub struct InstallPlan {
     pub target: Option<InstallTarget>,
     pub components: Vec<InstallModule>,
+    /// Variant chosen for components with per-accelerator packages
+    pub accelerator: Accelerator,
+    /// Package index the Python stage installs PyTorch from
+    pub pytorch_index: Option<String>,
+    /// How system packages get root; chosen per run, never saved
+    pub privilege: Privilege,
+    /// Rust toolchains, components and targets for Rust components
+    pub rust: RustSpec,
+}
+
+impl InstallPlan {
+    /// Record the selection so an interrupted run can be resumed
+    pub fn to_saved(&self) -> SavedPlan {
+        SavedPlan {
+            target: self.target.as_ref().map(|target| match target {
+                InstallTarget::Host => "host".to_string(),
+                InstallTarget::DevContainer => "devcontainer".to_string(),
+            }),
+            components: self.components.iter().map(|c| c.name.clone()).collect(),
+            accelerator: Some(self.accelerator.as_str().to_string()),
+            pytorch_index: self.pytorch_index.clone(),
+            rust: self.rust.clone(),
+        }
+    }
+
+    /// Switch to another accelerator variant, updating the PyTorch index
+    pub fn set_accelerator(&mut self, accelerator: Accelerator, hardware: &Hardware) {
+        self.accelerator = accelerator;
+        self.pytorch_index = hardware.pytorch_index_url(accelerator);
+    }
+
+    /// Whether any selected component depends on the accelerator choice
+    pub fn uses_accelerator(&self) -> bool {
+        self.components.iter().any(InstallModule::has_variants)
+    }
+
+    /// Whether any selected component sets up Rust
+    pub fn uses_rust(&self) -> bool {
+        self.components.iter().any(|c| c.toolchains.contains(&Toolchain::Rust))
+    }
+
+    /// Whether system packages are installed at all
+    ///
+    /// User-only mode keeps the rootless parts of each component, like
+    /// rustup, cargo crates and uv, and skips the system packages.
+    pub fn installs_system_packages(&self) -> bool {
+        self.privilege.can_elevate()
+    }
+
+    /// Rebuild the plan saved by an earlier run
+    pub fn from_saved(saved: &SavedPlan, privilege: Privilege) -> Result<Self> {
+        let target = match saved.target.as_deref() {
+            Some("host") => Some(InstallTarget::Host),
+            Some("devcontainer") => Some(InstallTarget::DevContainer),
+            Some(other) => return Err(anyhow!("Unknown install target: {}", other)),
+            None => None,
+        };
+
+        let available = get_available_components();
+        let components = saved
+            .components
+            .iter()
+            .map(|name| {
+                available
+                    .iter()
+                    .find(|c| &c.name == name)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Unknown component: {}", name))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Checkpoints from before variants existed are resolved again
+        let (accelerator, pytorch_index) = match saved.accelerator.as_deref() {
+            Some(accelerator) => (accelerator.parse()?, saved.pytorch_index.clone()),
+            None => {
+                let hardware = Hardware::detect();
+                let accelerator = hardware.accelerator();
+                (accelerator, hardware.pytorch_index_url(accelerator))
+            }
+        };
+
+        Ok(Self {
+            target,
+            components,
+            accelerator,
+            pytorch_index,
+            privilege,
+            rust: saved.rust.clone(),
+        })
+    }
 }
 
 fn get_available_components() -> Vec<InstallModule> {
@@ -49,15 +208,31 @@ fn get_available_components() -> Vec<InstallModule> {
                 "libssl-dev".to_string(),
                 "pkg-config".to_string(),
             ],
+            variants: vec![],
+            toolchains: vec![Toolchain::Rust],
+            services: vec![],
+            disk_mb: 3000,
             commands: vec![],
         },
         InstallModule {
             name: "Cyrup ML".to_string(),
-            description: "Machine Learning environment with CUDA support, Python ML libraries, and Jupyter.".to_string(),
-            dependencies: vec![
-                "python3-pip".to_string(),
-                "nvidia-cuda-toolkit".to_string(),
+            description: "Machine Learning environment with CUDA or ROCm support, Python ML libraries, and Jupyter.".to_string(),
+            dependencies: vec!["python3-pip".to_string()],
+            variants: vec![
+                (Accelerator::Cuda, vec!["nvidia-cuda-toolkit".to_string()]),
+                (
+                    Accelerator::Rocm,
+                    ["rocminfo", "rocm-smi", "hipcc", "libamdhip64-dev"]
+                        .iter()
+                        .map(|p| p.to_string())
+                        .collect(),
+                ),
+                (Accelerator::Metal, vec![]),
+                (Accelerator::Cpu, vec![]),
             ],
+            toolchains: vec![Toolchain::Python, Toolchain::Ml],
+            services: vec![],
+            disk_mb: 12000,
             commands: vec![],
         },
         InstallModule {
@@ -67,22 +242,47 @@ fn get_available_components() -> Vec<InstallModule> {
                 "python3-venv".to_string(),
                 "python3-dev".to_string(),
             ],
+            variants: vec![],
+            toolchains: vec![Toolchain::Python],
+            services: vec![],
+            disk_mb: 2000,
             commands: vec![],
         },
         InstallModule {
             name: "───────────────────".to_string(), // Separator
             description: "".to_string(),
             dependencies: vec![],
+            variants: vec![],
+            toolchains: vec![],
+            services: vec![],
+            disk_mb: 0,
             commands: vec![],
         },
         InstallModule {
             name: "Cyrup Developer".to_string(),
-            description: "Development environment with Rust, Python, and common development tools.".to_string(),
+            description: "Development environment with Rust, Python, Node.js, Go, and common development tools.".to_string(),
             dependencies: vec![
                 "build-essential".to_string(),
                 "git".to_string(),
                 "curl".to_string(),
             ],
+            variants: vec![],
+            toolchains: vec![Toolchain::Rust, Toolchain::Python, Toolchain::Node, Toolchain::Go, Toolchain::Releases],
+            services: vec![],
+            disk_mb: 5000,
+            commands: vec![],
+        },
+        InstallModule {
+            name: "Cyrup Database (PostgreSQL)".to_string(),
+            description: "PostgreSQL client and headers; Dev Containers also get a PostgreSQL service.".to_string(),
+            dependencies: vec![
+                "postgresql-client".to_string(),
+                "libpq-dev".to_string(),
+            ],
+            variants: vec![],
+            toolchains: vec![],
+            services: vec![Service::Postgres],
+            disk_mb: 500,
             commands: vec![],
         },
     ]
@@ -91,7 +291,7 @@ fn get_available_components() -> Vec<InstallModule> {
 fn get_target_description(target: &InstallTarget) -> String {
     match target {
         InstallTarget::Host => "Install directly on your host system.".to_string(),
-        InstallTarget::DevContainer => "Install in a Docker-based VS Code Dev Container for isolated development.".to_string(),
+        InstallTarget::DevContainer => "Generate a .devcontainer/ in this project to build and open in VS Code.".to_string(),
     }
 }
 
@@ -106,11 +306,15 @@ fn print_prompt() -> Result<()> {
     Ok(())
 }
 
-pub async fn show_module_menu() -> Result<InstallPlan> {
+pub async fn show_module_menu(privilege: Privilege) -> Result<InstallPlan> {
     print_prompt()?;
 
-    // First select components
-    let components = get_available_components();
+    // First select components; without root only those with rootless
+    // toolchains have anything left to install
+    let components: Vec<InstallModule> = get_available_components()
+        .into_iter()
+        .filter(|c| privilege.can_elevate() || !c.toolchains.is_empty())
+        .collect();
     let selected_components = MultiSelect::new("Select components to install:", components.clone())
         .with_help_message("↑↓ to move, space to select, enter to confirm")
         .with_formatter(&|items| {
@@ -134,48 +338,185 @@ pub async fn show_module_menu() -> Result<InstallPlan> {
 
     // If components were selected, choose installation target
     let target = if !selected_components.is_empty() {
-        let targets = vec![InstallTarget::Host, InstallTarget::DevContainer];
-        let selected = Select::new("Where would you like to install these components?", targets)
-            .with_help_message("↑↓ to move, enter to select")
-            .with_formatter(&|item| {
-                format!("{} - {}", item.value, get_target_description(item.value))
-            })
-            .prompt()?;
-        Some(selected)
+        let project = std::env::current_dir()?;
+        let mut targets = Environment::detect().targets(&project);
+        if targets.len() == 1 {
+            // Nothing to choose, e.g. inside a container
+            targets.pop()
+        } else {
+            let selected = Select::new("Where would you like to install these components?", targets)
+                .with_help_message("↑↓ to move, enter to select")
+                .with_formatter(&|item| {
+                    format!("{} - {}", item.value, get_target_description(item.value))
+                })
+                .prompt()?;
+            Some(selected)
+        }
     } else {
         None
     };
 
+    let hardware = Hardware::detect();
+    let accelerator = hardware.accelerator();
     Ok(InstallPlan {
         target,
         components: selected_components,
+        accelerator,
+        pytorch_index: hardware.pytorch_index_url(accelerator),
+        privilege,
+        rust: RustSpec::default(),
     })
 }
 
-pub fn confirm_installation(plan: &InstallPlan) -> Result<bool> {
+pub fn confirm_installation(plan: &mut InstallPlan) -> Result<bool> {
     if plan.components.is_empty() {
         println!("\nNo components selected for installation.");
         return Ok(false);
     }
 
+    let environment = Environment::detect();
+    let mut hardware = None;
+    loop {
+        print_plan(plan, &environment);
+
+        let change_accelerator = format!("Change accelerator (currently {})", plan.accelerator);
+        let change_rust = format!("Change Rust toolchain (currently {})", plan.rust.channel);
+        let mut options = vec!["Yes, proceed with installation".to_string()];
+        if plan.uses_accelerator() {
+            options.push(change_accelerator.clone());
+        }
+        if plan.uses_rust() {
+            options.push(change_rust.clone());
+        }
+        options.push("No, let me change my selection".to_string());
+
+        let answer = Select::new("Proceed with installation?", options).prompt()?;
+        if answer == change_accelerator {
+            let accelerator = Select::new("Build GPU components for:", Accelerator::ALL.to_vec())
+                .with_help_message("↑↓ to move, enter to select")
+                .prompt()?;
+            let hardware = hardware.get_or_insert_with(Hardware::detect);
+            plan.set_accelerator(accelerator, hardware);
+        } else if answer == change_rust {
+            plan.rust.channel = select_channel()?;
+        } else {
+            return Ok(answer.starts_with("Yes"));
+        }
+    }
+}
+
+/// Ask for the default Rust toolchain, which may be pinned
+fn select_channel() -> Result<Channel> {
+    const PINNED: &str = "Pinned release or dated nightly";
+    let options = vec!["stable", "beta", "nightly", PINNED];
+    let choice = Select::new("Default Rust toolchain:", options)
+        .with_help_message("↑↓ to move, enter to select")
+        .prompt()?;
+    if choice != PINNED {
+        return choice.parse();
+    }
+
+    let channel = Text::new("Rust toolchain:")
+        .with_help_message("e.g. 1.82.0 or nightly-2024-11-28")
+        .with_validator(|input: &str| {
+            Ok(match input.trim().parse::<Channel>() {
+                Ok(_) => Validation::Valid,
+                Err(e) => Validation::Invalid(e.to_string().into()),
+            })
+        })
+        .prompt()?;
+    channel.trim().parse()
+}
+
+fn print_plan(plan: &InstallPlan, environment: &Environment) {
     println!("\nInstallation Plan:");
-    
+
     // Show installation target if components were selected
     if let Some(target) = &plan.target {
         println!("\n🔹 Installation Target: {}", target);
         println!("  {}", get_target_description(target));
     }
 
+    if plan.uses_accelerator() {
+        println!("\n🔹 Accelerator: {}", plan.accelerator);
+        if let Some(index) = &plan.pytorch_index {
+            println!("  PyTorch index: {}", index);
+        }
+    }
+
+    if plan.uses_rust() {
+        println!("\n🔹 Rust toolchain: {}", plan.rust.channel);
+        if !plan.rust.extra_toolchains.is_empty() {
+            let extra: Vec<String> = plan.rust.extra_toolchains.iter().map(|t| t.to_string()).collect();
+            println!("  Also installed: {}", extra.join(", "));
+        }
+        println!("  Components: {}", plan.rust.components.join(", "));
+        println!("  Targets: {}", plan.rust.targets.join(", "));
+    }
+
+    if !plan.installs_system_packages() {
+        println!("\n🔹 User-only mode: system packages are skipped, install them separately");
+    }
+
+    let notes = environment.notes(plan);
+    if !notes.is_empty() {
+        println!("\n🔹 Environment:");
+        for note in notes {
+            println!("  • {}", note);
+        }
+    }
+
     // Show selected components
     println!("\n🔹 Selected Components:");
     for component in &plan.components {
         println!("  • {}", component.name);
         println!("    Description: {}", component.description);
-        println!("    Dependencies: {}", component.dependencies.join(", "));
+        println!(
+            "    Dependencies: {}",
+            component.dependencies_for(plan.accelerator).join(", ")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dependencies_follow_accelerator() {
+        let components = get_available_components();
+        let ml = components.iter().find(|c| c.name == "Cyrup ML").unwrap();
+
+        assert_eq!(
+            ml.dependencies_for(Accelerator::Cuda),
+            vec!["python3-pip", "nvidia-cuda-toolkit"]
+        );
+        let rocm = ml.dependencies_for(Accelerator::Rocm);
+        assert!(rocm.contains(&"hipcc".to_string()));
+        assert!(!rocm.contains(&"nvidia-cuda-toolkit".to_string()));
+        assert_eq!(ml.dependencies_for(Accelerator::Cpu), vec!["python3-pip"]);
+
+        let rust = &components[0];
+        assert!(!rust.has_variants());
+        assert_eq!(rust.dependencies_for(Accelerator::Cuda), rust.dependencies);
     }
 
-    let options = vec!["Yes, proceed with installation", "No, let me change my selection"];
-    let answer = Select::new("Proceed with installation?", options).prompt()?;
+    #[test]
+    fn test_saved_plan_round_trip() {
+        let saved = SavedPlan {
+            target: Some("host".to_string()),
+            components: vec!["Cyrup ML".to_string()],
+            accelerator: Some("rocm".to_string()),
+            pytorch_index: Some("https://download.pytorch.org/whl/rocm6.1".to_string()),
+            rust: RustSpec {
+                channel: Channel::Pinned("nightly-2024-11-28".to_string()),
+                ..RustSpec::default()
+            },
+        };
 
-    Ok(answer.starts_with("Yes"))
+        let plan = InstallPlan::from_saved(&saved, Privilege::Sudo).unwrap();
+        assert_eq!(plan.accelerator, Accelerator::Rocm);
+        assert!(plan.uses_accelerator());
+        assert_eq!(plan.to_saved(), saved);
+    }
 }
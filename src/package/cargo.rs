@@ -0,0 +1,357 @@
+use anyhow::{bail, Context, Result};
+use semver::{Version, VersionReq};
+
+/// Git reference to build a crate from
+#[derive(Debug, Clone, PartialEq)]
+pub enum GitRef {
+    Branch(String),
+    Tag(String),
+    Rev(String),
+}
+
+/// Where `cargo install` fetches a crate from
+#[derive(Debug, Clone, PartialEq)]
+pub enum CrateSource {
+    /// crates.io
+    Registry,
+    Git { url: String, reference: Option<GitRef> },
+}
+
+/// A crate installed with `cargo install`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrateSpec {
+    pub name: String,
+    /// Version requirement, e.g. "0.6" or "=0.6.1"; registry crates only
+    pub version: Option<String>,
+    pub features: Vec<String>,
+    pub no_default_features: bool,
+    pub source: CrateSource,
+    /// Build with the crate's own Cargo.lock
+    pub locked: bool,
+}
+
+impl CrateSpec {
+    /// Latest crates.io release, built with `--locked`
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: None,
+            features: Vec::new(),
+            no_default_features: false,
+            source: CrateSource::Registry,
+            locked: true,
+        }
+    }
+
+    /// Parse an entry like `dioxus-cli@0.6` or
+    /// `taplo-cli?git=https://github.com/tamasfe/taplo&rev=8f3e6a1&unlocked`
+    ///
+    /// Options after `?` are `git`, `branch`, `tag`, `rev`, `features`
+    /// (joined with `+`), `no-default-features` and `unlocked`.
+    pub fn parse(entry: &str) -> Result<Self> {
+        let (krate, options) = entry.split_once('?').unwrap_or((entry, ""));
+        let mut spec = match krate.split_once('@') {
+            Some((name, version)) => CrateSpec::new(name).with_version(version),
+            None => CrateSpec::new(krate),
+        };
+        if spec.name.is_empty() {
+            bail!("Missing crate name in {:?}", entry);
+        }
+
+        let mut url = None;
+        let mut reference = None;
+        for option in options.split('&').filter(|option| !option.is_empty()) {
+            match option.split_once('=') {
+                Some(("git", value)) => url = Some(value),
+                Some(("branch", value)) => reference = Some(GitRef::Branch(value.to_string())),
+                Some(("tag", value)) => reference = Some(GitRef::Tag(value.to_string())),
+                Some(("rev", value)) => reference = Some(GitRef::Rev(value.to_string())),
+                Some(("features", value)) => spec = spec.with_features(&value.split('+').collect::<Vec<_>>()),
+                None if option == "no-default-features" => spec = spec.without_default_features(),
+                None if option == "unlocked" => spec = spec.unlocked(),
+                _ => bail!("Unknown option {:?} in {:?}", option, entry),
+            }
+        }
+        match (url, reference) {
+            (Some(_), _) if spec.version.is_some() => bail!("{:?} sets both a version and a git source", entry),
+            (Some(url), reference) => spec = spec.with_git(url, reference),
+            (None, Some(_)) => bail!("{:?} sets a branch, tag or rev without a git source", entry),
+            (None, None) => {}
+        }
+        spec.version_req()?;
+        Ok(spec)
+    }
+
+    /// Restrict the installed version, using Cargo's requirement syntax
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    pub fn with_features(mut self, features: &[&str]) -> Self {
+        self.features = features.iter().map(|feature| feature.to_string()).collect();
+        self
+    }
+
+    pub fn without_default_features(mut self) -> Self {
+        self.no_default_features = true;
+        self
+    }
+
+    /// Build from a git repository, optionally at a branch, tag or rev
+    pub fn with_git(mut self, url: impl Into<String>, reference: Option<GitRef>) -> Self {
+        self.source = CrateSource::Git {
+            url: url.into(),
+            reference,
+        };
+        self
+    }
+
+    /// Resolve dependencies afresh instead of using the crate's lockfile
+    pub fn unlocked(mut self) -> Self {
+        self.locked = false;
+        self
+    }
+
+    /// The parsed version requirement, if any
+    pub fn version_req(&self) -> Result<Option<VersionReq>> {
+        self.version
+            .as_deref()
+            .map(|version| {
+                VersionReq::parse(version)
+                    .with_context(|| format!("Invalid version requirement {:?} for {}", version, self.name))
+            })
+            .transpose()
+    }
+
+    /// Arguments for `cargo install`
+    pub fn install_args(&self) -> Vec<String> {
+        let mut args = vec!["install".to_string()];
+        match &self.source {
+            CrateSource::Registry => {
+                if let Some(version) = &self.version {
+                    args.extend(["--version".to_string(), version.clone()]);
+                }
+            }
+            CrateSource::Git { url, reference } => {
+                args.extend(["--git".to_string(), url.clone()]);
+                match reference {
+                    Some(GitRef::Branch(branch)) => args.extend(["--branch".to_string(), branch.clone()]),
+                    Some(GitRef::Tag(tag)) => args.extend(["--tag".to_string(), tag.clone()]),
+                    Some(GitRef::Rev(rev)) => args.extend(["--rev".to_string(), rev.clone()]),
+                    None => {}
+                }
+            }
+        }
+        if !self.features.is_empty() {
+            args.extend(["--features".to_string(), self.features.join(",")]);
+        }
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+        if self.locked {
+            args.push("--locked".to_string());
+        }
+        args.push(self.name.clone());
+        args
+    }
+
+    /// Name for `cargo binstall`, with the version requirement if any
+    ///
+    /// Prebuilt binaries use the default features from crates.io, so
+    /// anything else has to be built from source.
+    pub fn binstall_name(&self) -> Option<String> {
+        let prebuilt = self.source == CrateSource::Registry && self.features.is_empty() && !self.no_default_features;
+        prebuilt.then(|| match &self.version {
+            Some(version) => format!("{}@{}", self.name, version),
+            None => self.name.clone(),
+        })
+    }
+
+    /// Whether one of the `installed` crates satisfies this spec
+    ///
+    /// Features cannot be checked, `cargo install --list` does not show
+    /// them. A registry spec needs a crates.io build; a git spec needs a
+    /// build from the same repository and, for a rev, that commit.
+    pub fn is_satisfied_by(&self, installed: &[InstalledCrate]) -> Result<bool> {
+        let version_req = self.version_req()?;
+        Ok(installed.iter().filter(|krate| krate.name == self.name).any(|krate| {
+            match (&self.source, &krate.source) {
+                (CrateSource::Registry, None) => version_req.as_ref().is_none_or(|req| req.matches(&krate.version)),
+                (CrateSource::Git { url, reference }, Some(source)) => git_source_matches(source, url, reference),
+                _ => false,
+            }
+        }))
+    }
+}
+
+/// Whether an installed git source like `git+https://github.com/o/r?branch=main#8f3e6a1b`
+/// was built from `url` at `reference`
+///
+/// The listing always has the commit, and the branch or tag only when one
+/// was asked for, so a branch or tag without one in the listing counts.
+fn git_source_matches(source: &str, url: &str, reference: &Option<GitRef>) -> bool {
+    let Some((location, commit)) = source.strip_prefix("git+").unwrap_or(source).split_once('#') else {
+        return false;
+    };
+    let (installed_url, query) = location.split_once('?').unwrap_or((location, ""));
+    let normalize = |url: &str| url.trim_end_matches('/').trim_end_matches(".git").to_string();
+    if normalize(installed_url) != normalize(url) {
+        return false;
+    }
+    match reference {
+        Some(GitRef::Rev(rev)) => commit.starts_with(rev.as_str()),
+        Some(GitRef::Branch(branch)) => query.is_empty() || query == format!("branch={}", branch),
+        Some(GitRef::Tag(tag)) => query.is_empty() || query == format!("tag={}", tag),
+        None => true,
+    }
+}
+
+/// A crate reported by `cargo install --list`
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledCrate {
+    pub name: String,
+    pub version: Version,
+    /// Git URL and commit, e.g. `git+https://github.com/o/r#8f3e6a1b`; `None` for crates.io
+    pub source: Option<String>,
+}
+
+/// Parse the output of `cargo install --list`
+///
+/// Crate lines look like `zellij v0.41.2:` or, for git installs,
+/// `foo v0.1.0 (git+https://github.com/o/r#8f3e6a1b):`. The indented
+/// lines below each crate list its binaries.
+pub fn parse_install_list(output: &str) -> Vec<InstalledCrate> {
+    output
+        .lines()
+        .filter(|line| !line.starts_with(char::is_whitespace))
+        .filter_map(|line| {
+            let line = line.trim_end().strip_suffix(':')?;
+            let (name, rest) = line.split_once(' ')?;
+            let (version, source) = match rest.split_once(' ') {
+                Some((version, source)) => (version, Some(source)),
+                None => (rest, None),
+            };
+            Some(InstalledCrate {
+                name: name.to_string(),
+                version: Version::parse(version.strip_prefix('v')?).ok()?,
+                source: source
+                    .and_then(|source| source.strip_prefix('(')?.strip_suffix(')'))
+                    .map(String::from),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSTALL_LIST: &str = "\
+cargo-edit v0.13.0:
+    cargo-add
+    cargo-upgrade
+dioxus-cli v0.6.1:
+    dx
+taplo-cli v0.9.3 (git+https://github.com/tamasfe/taplo#8f3e6a1b2c):
+    taplo
+zellij v0.42.0 (git+https://github.com/zellij-org/zellij?branch=main#c0ffee12):
+    zellij
+";
+
+    #[test]
+    fn test_install_args() {
+        let spec = CrateSpec::new("dioxus-cli").with_version("0.6").with_features(&["optimizations"]);
+        assert_eq!(
+            spec.install_args().join(" "),
+            "install --version 0.6 --features optimizations --locked dioxus-cli"
+        );
+        assert_eq!(spec.binstall_name(), None);
+
+        let spec = CrateSpec::new("cargo-edit").without_default_features().with_features(&["upgrade"]);
+        assert_eq!(
+            spec.install_args().join(" "),
+            "install --features upgrade --no-default-features --locked cargo-edit"
+        );
+
+        assert_eq!(CrateSpec::new("zellij").with_version("=0.41.2").binstall_name().unwrap(), "zellij@=0.41.2");
+    }
+
+    #[test]
+    fn test_install_args_for_git_sources() {
+        let spec = CrateSpec::new("taplo-cli")
+            .with_git("https://github.com/tamasfe/taplo", Some(GitRef::Rev("8f3e6a1".to_string())))
+            .without_default_features()
+            .unlocked();
+        assert_eq!(
+            spec.install_args().join(" "),
+            "install --git https://github.com/tamasfe/taplo --rev 8f3e6a1 --no-default-features taplo-cli"
+        );
+        assert_eq!(spec.binstall_name(), None);
+
+        let branch = CrateSpec::new("zellij").with_git("https://github.com/zellij-org/zellij", Some(GitRef::Branch("main".to_string())));
+        assert_eq!(
+            branch.install_args().join(" "),
+            "install --git https://github.com/zellij-org/zellij --branch main --locked zellij"
+        );
+        let tag = CrateSpec::new("zellij").with_git("https://github.com/zellij-org/zellij", Some(GitRef::Tag("v0.41.2".to_string())));
+        assert!(tag.install_args().join(" ").contains(" --tag v0.41.2 --locked "));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(CrateSpec::parse("dioxus-cli@0.6").unwrap(), CrateSpec::new("dioxus-cli").with_version("0.6"));
+        assert_eq!(
+            CrateSpec::parse("taplo-cli?git=https://github.com/tamasfe/taplo&rev=8f3e6a1&unlocked").unwrap(),
+            CrateSpec::new("taplo-cli")
+                .with_git("https://github.com/tamasfe/taplo", Some(GitRef::Rev("8f3e6a1".to_string())))
+                .unlocked()
+        );
+        assert_eq!(
+            CrateSpec::parse("cargo-edit?features=upgrade+set-version&no-default-features").unwrap(),
+            CrateSpec::new("cargo-edit").with_features(&["upgrade", "set-version"]).without_default_features()
+        );
+
+        assert!(CrateSpec::parse("?git=https://github.com/o/r").is_err());
+        assert!(CrateSpec::parse("zellij?branch=main").is_err());
+        assert!(CrateSpec::parse("zellij@0.41?git=https://github.com/zellij-org/zellij").is_err());
+        assert!(CrateSpec::parse("zellij?frozen").is_err());
+        assert!(CrateSpec::parse("zellij@not a version").is_err());
+    }
+
+    #[test]
+    fn test_is_satisfied_by_install_list() {
+        let installed = parse_install_list(INSTALL_LIST);
+        assert_eq!(installed.len(), 4);
+        assert_eq!(installed[1].version, Version::new(0, 6, 1));
+
+        let check = |spec: CrateSpec| spec.is_satisfied_by(&installed).unwrap();
+        assert!(check(CrateSpec::new("dioxus-cli").with_version("0.6")));
+        assert!(!check(CrateSpec::new("dioxus-cli").with_version("0.5")));
+        // A crate name that only appears inside another is not installed
+        assert!(!check(CrateSpec::new("cargo")));
+        // Built from git, not the crates.io release
+        assert!(!check(CrateSpec::new("taplo-cli")));
+
+        assert!(CrateSpec::new("zellij").with_version("not a version").version_req().is_err());
+    }
+
+    #[test]
+    fn test_is_satisfied_by_git_source() {
+        let installed = parse_install_list(INSTALL_LIST);
+        assert_eq!(
+            installed[2].source.as_deref(),
+            Some("git+https://github.com/tamasfe/taplo#8f3e6a1b2c")
+        );
+
+        let check = |spec: &str| CrateSpec::parse(spec).unwrap().is_satisfied_by(&installed).unwrap();
+        assert!(check("taplo-cli?git=https://github.com/tamasfe/taplo.git"));
+        assert!(check("taplo-cli?git=https://github.com/tamasfe/taplo&rev=8f3e6a1"));
+        assert!(!check("taplo-cli?git=https://github.com/tamasfe/taplo&rev=deadbeef"));
+        assert!(!check("taplo-cli?git=https://github.com/someone/taplo"));
+        assert!(check("zellij?git=https://github.com/zellij-org/zellij&branch=main"));
+        assert!(!check("zellij?git=https://github.com/zellij-org/zellij&branch=next"));
+        // The crates.io release is not a git build
+        assert!(!check("dioxus-cli?git=https://github.com/DioxusLabs/dioxus"));
+    }
+}
@@ -1,13 +1,9 @@
-use anyhow::{Context, Result};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
-use std::env;
 
-use super::{Package, PackageManager};
-use crate::system::run_cmd;
-
-const RYE_INSTALL_URL: &str = "https://rye-up.com/get";
-const RUSTUP_INSTALL_URL: &str = "https://sh.rustup.rs";
+use super::cargo::CrateSpec;
+use super::release::{Checksum, ReleaseSpec};
+use super::Package;
 
 /// Global catalog of common packages with their proper mappings
 pub static CATALOG: Lazy<HashMap<&'static str, Package>> = Lazy::new(|| {
@@ -34,7 +30,7 @@ pub static CATALOG: Lazy<HashMap<&'static str, Package>> = Lazy::new(|| {
         ]),
     );
 
-    // Python Base (for Rye)
+    // Python Base (native extensions built by uv)
     m.insert(
         "python-base",
         Package::new("build-essential").with_alternatives(vec![
@@ -125,7 +121,7 @@ pub fn get_base_packages() -> Vec<Package> {
 /// Get development environment packages
 pub fn get_dev_packages() -> Vec<Package> {
     get_many(&[
-        "python-base", // For Rye
+        "python-base", // For uv builds
         "rust-base",   // For rustup
         "shell-tools",
         "network-tools",
@@ -136,130 +132,58 @@ pub fn get_dev_packages() -> Vec<Package> {
 }
 
 /// Get list of cargo packages to install
-pub fn get_cargo_packages() -> Vec<&'static str> {
+pub fn get_cargo_packages() -> Vec<CrateSpec> {
     vec![
-        "dioxus-cli",
-        "hurl",
-        "ruff",
-        "pueue",
-        "zellij",
-        "zoxide",
-        "wasm-pack",
-        "taplo-cli",
-        "cargo-watch",
-        "cargo-edit",
-        "cargo-update",
+        // Our Dioxus projects are on 0.6
+        CrateSpec::new("dioxus-cli").with_version("0.6"),
+        CrateSpec::new("hurl"),
+        CrateSpec::new("pueue"),
+        CrateSpec::new("zellij"),
+        CrateSpec::new("zoxide"),
+        CrateSpec::new("wasm-pack"),
+        CrateSpec::new("taplo-cli"),
+        CrateSpec::new("cargo-watch"),
+        // cargo has shipped `cargo add` and `cargo rm` itself since 1.62
+        CrateSpec::new("cargo-edit")
+            .without_default_features()
+            .with_features(&["upgrade", "set-version"]),
+        CrateSpec::new("cargo-update"),
     ]
 }
 
-/// Setup Python environment using Rye and uv
-pub fn setup_python() -> Result<()> {
-    println!("Installing Python development environment...");
-
-    // Install base Python requirements
-    let packages = get_many(&["python-base"]);
-    let pm = PackageManager::detect()?;
-    pm.install(&packages)?;
-
-    // Download and run Rye installer
-    let temp_dir = env::temp_dir();
-    let installer = temp_dir.join("rye-installer.sh");
-    
-    println!("Installing Rye for Python management...");
-    run_cmd(
-        "curl",
-        &["-sSf", RYE_INSTALL_URL, "-o", installer.to_str().unwrap()],
-    ).context("Failed to download Rye installer")?;
-
-    // Make installer executable and run it
-    run_cmd("chmod", &["+x", installer.to_str().unwrap()])?;
-    run_cmd(installer.to_str().unwrap(), &[]).context("Failed to run Rye installer")?;
-
-    // Add Rye to PATH
-    let home = env::var("HOME").context("HOME environment variable not set")?;
-    let rye_bin = format!("{home}/.rye/shims");
-    
-    // Update both .bashrc and .zshrc if they exist
-    let path_export = format!("export PATH=\"{rye_bin}:$PATH\"");
-    for rc_file in &[".bashrc", ".zshrc"] {
-        let rc_path = format!("{home}/{rc_file}");
-        if std::path::Path::new(&rc_path).exists() {
-            run_cmd("sh", &["-c", &format!("echo '{path_export}' >> {rc_path}")])?;
-        }
-    }
-
-    // Initialize rye and configure it
-    println!("Configuring Rye and installing Python toolchain...");
-    run_cmd("rye", &["self", "update"]).context("Failed to update Rye")?;
-    run_cmd("rye", &["toolchain", "install", "3.11"]).context("Failed to install Python toolchain")?;
-    run_cmd("rye", &["config", "set", "behavior.global-python=true"]).context("Failed to configure Rye")?;
-
-    // Install and configure uv
-    println!("Installing uv package installer...");
-    run_cmd("rye", &["install", "uv"]).context("Failed to install uv")?;
-    run_cmd("rye", &["config", "set", "pip.use-uv", "true"]).context("Failed to configure uv")?;
-
-    Ok(())
+/// Default tools for `go install`, as `package@version`
+pub fn get_go_tools() -> Vec<&'static str> {
+    vec![
+        "golang.org/x/tools/gopls@latest",
+        "github.com/go-delve/delve/cmd/dlv@latest",
+        "honnef.co/go/tools/cmd/staticcheck@latest",
+    ]
 }
 
-/// Setup Rust environment using rustup
-pub fn setup_rust() -> Result<()> {
-    println!("Installing Rust development environment...");
-
-    // Install base Rust requirements
-    let packages = get_many(&["build-essential"]);
-    let pm = PackageManager::detect()?;
-    pm.install(&packages)?;
-
-    // Download and run rustup installer
-    let temp_dir = env::temp_dir();
-    let installer = temp_dir.join("rustup-init.sh");
-    
-    println!("Installing Rust toolchain via rustup...");
-    run_cmd(
-        "curl",
-        &[
-            "--proto",
-            "'=https'",
-            "--tlsv1.2",
-            "-sSf",
-            RUSTUP_INSTALL_URL,
-            "-o",
-            installer.to_str().unwrap(),
-        ],
-    ).context("Failed to download rustup")?;
-
-    // Make installer executable and run it
-    run_cmd("chmod", &["+x", installer.to_str().unwrap()])?;
-    run_cmd(installer.to_str().unwrap(), &["-y", "--no-modify-path"])
-        .context("Failed to run rustup installer")?;
-
-    // Add cargo bin to PATH
-    let home = env::var("HOME").context("HOME environment variable not set")?;
-    let cargo_bin = format!("{home}/.cargo/bin");
-    
-    // Update both .bashrc and .zshrc if they exist
-    let path_export = format!("export PATH=\"{cargo_bin}:$PATH\"");
-    for rc_file in &[".bashrc", ".zshrc"] {
-        let rc_path = format!("{home}/{rc_file}");
-        if std::path::Path::new(&rc_path).exists() {
-            run_cmd("sh", &["-c", &format!("echo '{path_export}' >> {rc_path}")])?;
-        }
-    }
-
-    // Source cargo env for current session
-    run_cmd("sh", &["-c", "source $HOME/.cargo/env"]).context("Failed to source cargo env")?;
-
-    // Install cargo packages
-    println!("Installing cargo tools...");
-    let packages = get_cargo_packages();
-    for pkg in packages {
-        println!("Installing cargo package: {}", pkg);
-        run_cmd("cargo", &["install", pkg, "--locked"])
-            .with_context(|| format!("Failed to install {}", pkg))?;
-    }
-
-    Ok(())
+/// Tools installed from their GitHub release binaries
+pub fn get_release_binaries() -> Vec<ReleaseSpec> {
+    vec![
+        ReleaseSpec::new(
+            "starship",
+            "1.21.1",
+            "https://github.com/starship/starship/releases/download/v{version}/starship-{arch}-{os}.tar.gz",
+            Checksum::Sidecar(".sha256".to_string()),
+        )
+        .with_os_name("linux", "unknown-linux-musl")
+        .with_os_name("darwin", "apple-darwin")
+        .with_arch_name("amd64", "x86_64")
+        .with_arch_name("arm64", "aarch64"),
+        ReleaseSpec::new(
+            "just",
+            "1.38.0",
+            "https://github.com/casey/just/releases/download/{version}/just-{version}-{arch}-{os}.tar.gz",
+            Checksum::List("https://github.com/casey/just/releases/download/{version}/SHA256SUMS".to_string()),
+        )
+        .with_os_name("linux", "unknown-linux-musl")
+        .with_os_name("darwin", "apple-darwin")
+        .with_arch_name("amd64", "x86_64")
+        .with_arch_name("arm64", "aarch64"),
+    ]
 }
 
 #[cfg(test)]
@@ -276,6 +200,13 @@ mod tests {
         assert!(packages.contains(&"htop".to_string()));
     }
 
+    #[test]
+    fn test_cargo_version_requirements_parse() {
+        for spec in get_cargo_packages() {
+            assert!(spec.version_req().is_ok(), "{}", spec.name);
+        }
+    }
+
     #[test]
     fn test_dev_packages() {
         let packages = get_dev_packages();
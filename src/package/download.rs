@@ -0,0 +1,93 @@
+use anyhow::{anyhow, Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crate::system::run_cmd;
+
+/// curl arguments for `url`
+///
+/// Only HTTPS is allowed, except for loopback addresses like local test
+/// fixtures.
+fn curl_args(url: &str) -> Vec<&str> {
+    let loopback = ["http://127.0.0.1:", "http://localhost:"]
+        .iter()
+        .any(|prefix| url.starts_with(prefix));
+    let protocols = if loopback { "=http" } else { "=https" };
+    vec!["--proto", protocols, "--tlsv1.2", "-fsSL"]
+}
+
+/// Download `url` to `dest`
+pub fn fetch(url: &str, dest: &Path) -> Result<()> {
+    let dest = dest.to_str().ok_or_else(|| anyhow!("Invalid download path"))?;
+    let mut args = curl_args(url);
+    args.extend([url, "-o", dest]);
+    run_cmd("curl", &args).with_context(|| format!("Failed to download {}", url))
+}
+
+/// Download `url` and return the body, e.g. a published checksum
+pub fn fetch_text(url: &str) -> Result<String> {
+    let output = Command::new("curl")
+        .args(curl_args(url))
+        .arg(url)
+        .output()
+        .context("Failed to run curl")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to download {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Lowercase hex SHA-256 of the file at `path`
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Fail unless `path` hashes to `expected`
+///
+/// `expected` may be a whole `.sha256` file; only its first word is used.
+pub fn verify_sha256(path: &Path, expected: &str) -> Result<()> {
+    let expected = expected
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| anyhow!("Empty checksum for {}", path.display()))?;
+    let actual = sha256_file(path)?;
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(anyhow!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            path.display(),
+            expected,
+            actual
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_verify_sha256() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("archive.tar.gz");
+        fs::write(&path, "abc").unwrap();
+
+        let digest = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(sha256_file(&path).unwrap(), digest);
+        assert!(verify_sha256(&path, &format!("{}  archive.tar.gz\n", digest)).is_ok());
+        assert!(verify_sha256(&path, &digest.to_uppercase()).is_ok());
+        assert!(verify_sha256(&path, &"0".repeat(64)).is_err());
+        assert!(verify_sha256(&path, "").is_err());
+    }
+}
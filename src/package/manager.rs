@@ -1,12 +1,14 @@
 use anyhow::{anyhow, Result};
 use std::fmt;
+use std::process::{Command, Stdio};
 use which::which;
 
 use super::recipe::Recipe;
+use super::privilege::Privilege;
 use super::Package;
 
 /// Supported package managers
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PackageManager {
     /// Debian-based systems (Debian, Ubuntu, etc.)
     Apt,
@@ -36,28 +38,67 @@ impl PackageManager {
     }
 
     /// Get the recipe for this package manager
-    pub fn recipe(&self) -> Recipe {
-        match self {
-            PackageManager::Apt => Recipe::new()
-                .with_update_steps(vec![
-                    ("sudo", vec!["apt-get", "-y", "clean"]),
-                    ("sudo", vec!["apt-get", "-y", "update"]),
-                ])
-                .with_install_step("sudo", vec!["apt-get", "install", "-y"]),
-            PackageManager::Yum => Recipe::new()
-                .with_update_steps(vec![
-                    ("sudo", vec!["yum", "-y", "clean", "all"]),
-                    ("sudo", vec!["yum", "-y", "makecache"]),
-                ])
-                .with_install_step("sudo", vec!["yum", "install", "-y"]),
+    ///
+    /// apt-get and yum run as root through `privilege`, so there is no
+    /// recipe for them in user-only mode.
+    pub fn recipe(&self, privilege: Privilege) -> Result<Recipe> {
+        let elevated = |args: Vec<&'static str>| {
+            privilege
+                .elevate(args)
+                .ok_or_else(|| anyhow!("Installing {} packages needs root, which user-only mode does not have", self))
+        };
+
+        Ok(match self {
+            PackageManager::Apt => {
+                let (cmd, args) = elevated(vec!["apt-get", "install", "-y"])?;
+                Recipe::new()
+                    .with_update_steps(vec![
+                        elevated(vec!["apt-get", "-y", "clean"])?,
+                        elevated(vec!["apt-get", "-y", "update"])?,
+                    ])
+                    .with_install_step(cmd, args)
+            }
+            PackageManager::Yum => {
+                let (cmd, args) = elevated(vec!["yum", "install", "-y"])?;
+                Recipe::new()
+                    .with_update_steps(vec![
+                        elevated(vec!["yum", "-y", "clean", "all"])?,
+                        elevated(vec!["yum", "-y", "makecache"])?,
+                    ])
+                    .with_install_step(cmd, args)
+            }
+            // Homebrew refuses to run as root
             PackageManager::Brew => Recipe::new()
                 .with_update_steps(vec![("brew", vec!["update"])])
                 .with_install_step("brew", vec!["install"]),
+        })
+    }
+
+    /// Check whether every package name for `package` is already installed
+    pub fn is_installed(&self, package: &Package) -> Result<bool> {
+        for name in package.names_for(self) {
+            let (cmd, args) = match self {
+                PackageManager::Apt => ("dpkg", vec!["-s", name]),
+                PackageManager::Yum => ("rpm", vec!["-q", name]),
+                PackageManager::Brew => ("brew", vec!["list", name]),
+            };
+
+            let installed = Command::new(cmd)
+                .args(&args)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .map(|status| status.success())
+                .unwrap_or(false);
+            if !installed {
+                return Ok(false);
+            }
         }
+        Ok(true)
     }
 
     /// Install packages using this package manager
-    pub fn install(&self, packages: &[Package]) -> Result<()> {
+    pub fn install(&self, packages: &[Package], privilege: Privilege) -> Result<()> {
         if packages.is_empty() {
             return Ok(());
         }
@@ -65,7 +106,7 @@ impl PackageManager {
         // Split packages into batches of 10 to avoid command line length limits
         const BATCH_SIZE: usize = 10;
         for chunk in packages.chunks(BATCH_SIZE) {
-            let recipe = self.recipe();
+            let recipe = self.recipe(privilege)?;
             recipe.execute(self, chunk)?;
         }
         Ok(())
@@ -95,19 +136,25 @@ mod tests {
 
     #[test]
     fn test_recipe_generation() {
-        let apt_recipe = PackageManager::Apt.recipe();
+        let apt_recipe = PackageManager::Apt.recipe(Privilege::Sudo).unwrap();
         assert_eq!(apt_recipe.install_command.0, "sudo");
         assert_eq!(
             apt_recipe.install_command.1,
             vec!["apt-get", "install", "-y"]
         );
 
-        let yum_recipe = PackageManager::Yum.recipe();
-        assert_eq!(yum_recipe.install_command.0, "sudo");
+        let yum_recipe = PackageManager::Yum.recipe(Privilege::Doas).unwrap();
+        assert_eq!(yum_recipe.install_command.0, "doas");
         assert_eq!(yum_recipe.install_command.1, vec!["yum", "install", "-y"]);
 
-        let brew_recipe = PackageManager::Brew.recipe();
+        let brew_recipe = PackageManager::Brew.recipe(Privilege::Sudo).unwrap();
         assert_eq!(brew_recipe.install_command.0, "brew");
         assert_eq!(brew_recipe.install_command.1, vec!["install"]);
+
+        let root_recipe = PackageManager::Apt.recipe(Privilege::Root).unwrap();
+        assert_eq!(root_recipe.install_command.0, "apt-get");
+        assert_eq!(root_recipe.install_command.1, vec!["install", "-y"]);
+
+        assert!(PackageManager::Apt.recipe(Privilege::UserOnly).is_err());
     }
 }
@@ -1,12 +1,17 @@
+pub mod cargo;
 pub mod catalog;
+pub mod download;
 pub mod manager;
+pub mod privilege;
 pub mod recipe;
+pub mod release;
 pub mod sys;
 
 use std::fmt;
 
 // Re-export commonly used items
 pub use manager::PackageManager;
+pub use privilege::Privilege;
 pub use recipe::Recipe;
 pub use sys::PackageCache;
 
@@ -67,6 +72,14 @@ impl Package {
                 .unwrap_or(&self.name),
         }
     }
+
+    /// Get the individual package names for the given package manager
+    ///
+    /// Catalog entries map to several space separated packages, e.g.
+    /// `apt:build-essential pkg-config`.
+    pub fn names_for(&self, pm: &manager::PackageManager) -> Vec<&str> {
+        self.name_for(pm).split_whitespace().collect()
+    }
 }
 
 impl fmt::Display for Package {
@@ -94,6 +107,18 @@ mod tests {
         assert_eq!(pkg.name_for(&PackageManager::Brew), "git");
     }
 
+    #[test]
+    fn test_package_names_for() {
+        let pkg = Package::new("build-essential")
+            .with_alternatives(vec!["apt:build-essential pkg-config".to_string()]);
+
+        assert_eq!(
+            pkg.names_for(&PackageManager::Apt),
+            vec!["build-essential", "pkg-config"]
+        );
+        assert_eq!(pkg.names_for(&PackageManager::Yum), vec!["build-essential"]);
+    }
+
     #[test]
     fn test_package_display() {
         let pkg = Package::new("git");
@@ -0,0 +1,122 @@
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::process::Command;
+use which::which;
+
+use crate::environment::Environment;
+
+/// How commands that need root are run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Privilege {
+    /// Already root, run commands directly
+    Root,
+    Sudo,
+    /// OpenBSD style `doas`, common on Alpine
+    Doas,
+    /// No root access; system packages are skipped
+    UserOnly,
+}
+
+impl Privilege {
+    /// Pick the strategy for this run
+    ///
+    /// Falls back to user-only mode when neither sudo nor doas exist.
+    pub fn detect(user_only: bool) -> Self {
+        if user_only {
+            Privilege::UserOnly
+        } else if Environment::detect().is_root {
+            Privilege::Root
+        } else if which("sudo").is_ok() {
+            Privilege::Sudo
+        } else if which("doas").is_ok() {
+            Privilege::Doas
+        } else {
+            Privilege::UserOnly
+        }
+    }
+
+    /// Whether commands can be run as root at all
+    pub fn can_elevate(&self) -> bool {
+        *self != Privilege::UserOnly
+    }
+
+    /// Turn `args` into a command that runs as root
+    ///
+    /// Returns `None` in user-only mode.
+    pub fn elevate(&self, args: Vec<&'static str>) -> Option<(&'static str, Vec<&'static str>)> {
+        match self {
+            Privilege::Root => Some((args[0], args[1..].to_vec())),
+            Privilege::Sudo => Some(("sudo", args)),
+            Privilege::Doas => Some(("doas", args)),
+            Privilege::UserOnly => None,
+        }
+    }
+
+    /// Make sure elevated commands will not stop to ask for a password
+    ///
+    /// Prompts once up front so credentials are cached before the UI
+    /// takes over the terminal.
+    pub fn ensure_access(&self) -> Result<()> {
+        let tool = match self {
+            Privilege::Root => return Ok(()),
+            Privilege::Sudo => "sudo",
+            Privilege::Doas => "doas",
+            Privilege::UserOnly => {
+                return Err(anyhow!("Running in user-only mode, root access is not available"))
+            }
+        };
+
+        which(tool).map_err(|_| anyhow!("{} is not available", tool))?;
+
+        // Try cached credentials first
+        let output = Command::new(tool)
+            .args(["-n", "true"])
+            .output()
+            .map_err(|e| anyhow!("Failed to execute {}: {}", tool, e))?;
+
+        if !output.status.success() {
+            println!("⚡ Administrator access required for system setup ({})", tool);
+            println!("🔐 Please enter your password when prompted");
+
+            let status = Command::new(tool)
+                .arg("true")
+                .status()
+                .map_err(|e| anyhow!("Failed to execute {}: {}", tool, e))?;
+
+            if !status.success() {
+                return Err(anyhow!("Failed to obtain {} access", tool));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Privilege {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Privilege::Root => write!(f, "root"),
+            Privilege::Sudo => write!(f, "sudo"),
+            Privilege::Doas => write!(f, "doas"),
+            Privilege::UserOnly => write!(f, "user-only"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elevate() {
+        let args = vec!["apt-get", "install", "-y"];
+        assert_eq!(Privilege::Root.elevate(args.clone()), Some(("apt-get", vec!["install", "-y"])));
+        assert_eq!(
+            Privilege::Doas.elevate(args.clone()),
+            Some(("doas", vec!["apt-get", "install", "-y"]))
+        );
+        assert_eq!(Privilege::UserOnly.elevate(args), None);
+        assert!(Privilege::UserOnly.ensure_access().is_err());
+        assert_eq!(Privilege::detect(true), Privilege::UserOnly);
+    }
+}
@@ -1,8 +1,8 @@
 use anyhow::{Context, Result};
-use std::process::Command;
 use std::time::Duration;
 
 use super::{manager::PackageManager, Package};
+use crate::logging;
 
 const MAX_RETRIES: u32 = 3;
 const INITIAL_RETRY_DELAY_MS: u64 = 1000;
@@ -66,11 +66,7 @@ impl Recipe {
 
         // Add package names, using the appropriate name for this package manager
         for package in packages {
-            let pkg_name = package.name_for(pm);
-            if pkg_name.contains(char::is_whitespace) {
-                return Err(anyhow::anyhow!("Invalid package name (contains whitespace): {}", pkg_name));
-            }
-            install_args.push(pkg_name);
+            install_args.extend(package.names_for(pm));
         }
 
         self.retry_with_backoff(|| self.run_cmd(cmd, &install_args), MAX_RETRIES)
@@ -81,8 +77,6 @@ impl Recipe {
 
     /// Run a system command
     fn run_cmd(&self, cmd: &str, args: &[&str]) -> Result<()> {
-        println!("Running: {} {:?}", cmd, args);
-
         // Validate command and arguments
         if cmd.is_empty() {
             return Err(anyhow::anyhow!("Empty command"));
@@ -93,34 +87,7 @@ impl Recipe {
             }
         }
 
-        let output = Command::new(cmd)
-            .args(args)
-            .output()
-            .with_context(|| format!("Failed to execute command: {} {:?}", cmd, args))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-
-        // Print output in real-time for better debugging
-        if !stdout.is_empty() {
-            println!("stdout:\n{}", stdout);
-        }
-        if !stderr.is_empty() {
-            eprintln!("stderr:\n{}", stderr);
-        }
-
-        if !output.status.success() {
-            return Err(anyhow::anyhow!(
-                "Command failed with status {}: {} {:?}\nstdout: {}\nstderr: {}",
-                output.status,
-                cmd,
-                args,
-                stdout,
-                stderr
-            ));
-        }
-
-        Ok(())
+        crate::system::run_cmd(cmd, args)
     }
 
     /// Retry a command with exponential backoff
@@ -138,9 +105,9 @@ impl Recipe {
                     if retries >= max_retries {
                         return Err(e.context("Exceeded maximum retry attempts"));
                     }
-                    eprintln!("Command failed, retrying in {}ms: {}", delay, e);
-                    std::thread::sleep(Duration::from_millis(delay));
                     retries += 1;
+                    logging::retry(retries, max_retries, Duration::from_millis(delay), &e);
+                    std::thread::sleep(Duration::from_millis(delay));
                     delay *= 2; // Exponential backoff
                 }
             }
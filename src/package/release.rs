@@ -0,0 +1,419 @@
+use anyhow::{anyhow, Context, Result};
+use dirs::home_dir;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::download;
+use crate::system::run_cmd;
+
+/// Held across each read-modify-write of a state file
+///
+/// Release steps run in parallel; only their version bookkeeping has to
+/// take turns.
+static STATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Where the expected SHA-256 of a release asset is published
+#[derive(Debug, Clone, PartialEq)]
+pub enum Checksum {
+    /// Next to the asset, at its URL plus a suffix like `.sha256`
+    Sidecar(String),
+    /// One file listing `<sha256>  <asset>` lines, a URL template
+    List(String),
+}
+
+/// A tool installed from a prebuilt release asset
+///
+/// URL templates use `{version}`, `{os}` and `{arch}`. `os` and `arch`
+/// start out as `sys::detect_platform` names (linux/darwin, amd64/arm64)
+/// and can be renamed to whatever the project uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReleaseSpec {
+    pub name: String,
+    pub version: String,
+    pub url: String,
+    pub checksum: Checksum,
+    pub os_names: Vec<(String, String)>,
+    pub arch_names: Vec<(String, String)>,
+}
+
+impl ReleaseSpec {
+    pub fn new(
+        name: impl Into<String>,
+        version: impl Into<String>,
+        url: impl Into<String>,
+        checksum: Checksum,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            url: url.into(),
+            checksum,
+            os_names: Vec::new(),
+            arch_names: Vec::new(),
+        }
+    }
+
+    /// Name the project uses for an OS, e.g. `darwin` -> `apple-darwin`
+    pub fn with_os_name(mut self, os: &str, name: &str) -> Self {
+        self.os_names.push((os.to_string(), name.to_string()));
+        self
+    }
+
+    /// Name the project uses for an architecture, e.g. `amd64` -> `x86_64`
+    pub fn with_arch_name(mut self, arch: &str, name: &str) -> Self {
+        self.arch_names.push((arch.to_string(), name.to_string()));
+        self
+    }
+
+    /// Executable name, in the archive and in the bin directory
+    pub fn binary_name(&self) -> &str {
+        &self.name
+    }
+
+    fn expand(&self, template: &str, os: &str, arch: &str) -> String {
+        let rename = |names: &[(String, String)], value: &str| {
+            names
+                .iter()
+                .find(|(from, _)| from == value)
+                .map_or(value.to_string(), |(_, to)| to.clone())
+        };
+        template
+            .replace("{version}", &self.version)
+            .replace("{os}", &rename(&self.os_names, os))
+            .replace("{arch}", &rename(&self.arch_names, arch))
+    }
+
+    /// Asset URL for a `detect_platform` os/arch pair
+    pub fn asset_url(&self, os: &str, arch: &str) -> String {
+        self.expand(&self.url, os, arch)
+    }
+
+    /// URL of the published checksum for that asset
+    pub fn checksum_url(&self, os: &str, arch: &str) -> String {
+        match &self.checksum {
+            Checksum::Sidecar(suffix) => format!("{}{}", self.asset_url(os, arch), suffix),
+            Checksum::List(template) => self.expand(template, os, arch),
+        }
+    }
+}
+
+/// The checksum for `asset` in a published checksum file
+///
+/// Sidecar files often hold just the digest; lists hold `<digest>  <asset>`
+/// lines, with `*` marking binary mode in sha256sum output.
+pub fn checksum_for(published: &str, asset: &str) -> Option<String> {
+    published.lines().find_map(|line| {
+        let mut words = line.split_whitespace();
+        let digest = words.next()?;
+        match words.next() {
+            None => Some(digest.to_string()),
+            Some(file) => {
+                let file = file.trim_start_matches('*');
+                (file == asset || file.rsplit('/').next() == Some(asset)).then(|| digest.to_string())
+            }
+        }
+    })
+}
+
+/// How a release asset is packaged, from its file name
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Archive {
+    Tar,
+    Zip,
+    /// The asset is the executable itself
+    Binary,
+}
+
+impl Archive {
+    pub(crate) fn from_asset(asset: &str) -> Self {
+        if [".tar.gz", ".tgz", ".tar.xz", ".tar.bz2"].iter().any(|ext| asset.ends_with(ext)) {
+            Archive::Tar
+        } else if asset.ends_with(".zip") {
+            Archive::Zip
+        } else {
+            Archive::Binary
+        }
+    }
+}
+
+/// Find the file called `name` anywhere under `dir`
+fn find_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    fs::read_dir(dir).ok()?.flatten().find_map(|entry| {
+        let path = entry.path();
+        if path.is_dir() {
+            find_file(&path, name)
+        } else {
+            (entry.file_name() == name).then_some(path)
+        }
+    })
+}
+
+/// Installs release binaries and remembers which version each one is
+pub struct ReleaseInstaller {
+    bin_dir: PathBuf,
+    /// JSON map of tool name to installed version
+    state_file: PathBuf,
+}
+
+impl ReleaseInstaller {
+    /// Binaries in `~/.local/bin`, versions in `~/.local/share/cyrup/releases.json`
+    pub fn new() -> Result<Self> {
+        let home = home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+        Ok(Self::with_dirs(
+            home.join(".local/bin"),
+            home.join(".local/share/cyrup/releases.json"),
+        ))
+    }
+
+    pub fn with_dirs(bin_dir: PathBuf, state_file: PathBuf) -> Self {
+        Self { bin_dir, state_file }
+    }
+
+    pub fn bin_dir(&self) -> &Path {
+        &self.bin_dir
+    }
+
+    pub fn binary_path(&self, spec: &ReleaseSpec) -> PathBuf {
+        self.bin_dir.join(spec.binary_name())
+    }
+
+    fn versions(&self) -> BTreeMap<String, String> {
+        fs::read_to_string(&self.state_file)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Apply `change` to the recorded versions, saving them if it returns true
+    fn update_versions(&self, change: impl FnOnce(&mut BTreeMap<String, String>) -> bool) -> Result<()> {
+        // A panic elsewhere cannot leave the map itself half updated
+        let _guard = STATE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut versions = self.versions();
+        if change(&mut versions) {
+            self.save_versions(&versions)?;
+        }
+        Ok(())
+    }
+
+    fn save_versions(&self, versions: &BTreeMap<String, String>) -> Result<()> {
+        if let Some(parent) = self.state_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write beside the state file and rename, so a crash mid-write
+        // never leaves a truncated releases.json behind
+        let staged = self.state_file.with_extension("json.tmp");
+        fs::write(&staged, serde_json::to_string_pretty(versions)?)
+            .with_context(|| format!("Failed to write {}", staged.display()))?;
+        fs::rename(&staged, &self.state_file)
+            .with_context(|| format!("Failed to write {}", self.state_file.display()))
+    }
+
+    /// Version recorded when `name` was last installed
+    pub fn installed_version(&self, name: &str) -> Option<String> {
+        self.versions().remove(name)
+    }
+
+    /// Whether the spec's version is installed; a different one needs an upgrade
+    pub fn is_current(&self, spec: &ReleaseSpec) -> bool {
+        self.binary_path(spec).exists()
+            && self.installed_version(&spec.name).as_deref() == Some(spec.version.as_str())
+    }
+
+    /// Download, verify and install the asset for the os/arch pair
+    ///
+    /// Nothing in the bin directory changes unless the checksum matches.
+    pub fn install(&self, spec: &ReleaseSpec, os: &str, arch: &str) -> Result<()> {
+        let url = spec.asset_url(os, arch);
+        let asset = url.rsplit('/').next().unwrap_or(&spec.name).to_string();
+        let work = env::temp_dir().join(format!("cyrup-release-{}-{}", spec.name, std::process::id()));
+        if work.exists() {
+            fs::remove_dir_all(&work)?;
+        }
+        fs::create_dir_all(&work)?;
+
+        let result = self.install_from(spec, &url, &asset, &work, os, arch);
+        let _ = fs::remove_dir_all(&work);
+        result
+    }
+
+    fn install_from(&self, spec: &ReleaseSpec, url: &str, asset: &str, work: &Path, os: &str, arch: &str) -> Result<()> {
+        let download_path = work.join(asset);
+        download::fetch(url, &download_path)?;
+
+        let published = download::fetch_text(&spec.checksum_url(os, arch))?;
+        let expected = checksum_for(&published, asset)
+            .ok_or_else(|| anyhow!("No published checksum for {}", asset))?;
+        download::verify_sha256(&download_path, &expected)?;
+
+        let extracted = work.join("extracted");
+        fs::create_dir_all(&extracted)?;
+        let archive = download_path.to_str().ok_or_else(|| anyhow!("Invalid download path"))?;
+        let into = extracted.to_str().ok_or_else(|| anyhow!("Invalid download path"))?;
+        let binary = match Archive::from_asset(asset) {
+            Archive::Tar => {
+                run_cmd("tar", &["-xf", archive, "-C", into])
+                    .with_context(|| format!("Failed to unpack {}", asset))?;
+                find_file(&extracted, spec.binary_name())
+            }
+            Archive::Zip => {
+                run_cmd("unzip", &["-q", "-o", archive, "-d", into])
+                    .with_context(|| format!("Failed to unpack {}", asset))?;
+                find_file(&extracted, spec.binary_name())
+            }
+            Archive::Binary => Some(download_path.clone()),
+        }
+        .ok_or_else(|| anyhow!("{} does not contain {}", asset, spec.binary_name()))?;
+
+        fs::create_dir_all(&self.bin_dir)?;
+        let target = self.binary_path(spec);
+        // Copy next to the target and rename, so a running binary is never half written
+        let staged = self.bin_dir.join(format!(".{}.setcyrup", spec.binary_name()));
+        fs::copy(&binary, &staged).with_context(|| format!("Failed to copy {}", spec.binary_name()))?;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+        fs::rename(&staged, &target).with_context(|| format!("Failed to install {}", target.display()))?;
+
+        self.update_versions(|versions| {
+            versions.insert(spec.name.clone(), spec.version.clone());
+            true
+        })
+    }
+
+    /// Remove the binary and forget its version
+    pub fn uninstall(&self, spec: &ReleaseSpec) -> Result<()> {
+        let path = self.binary_path(spec);
+        if path.exists() {
+            fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        self.update_versions(|versions| versions.remove(&spec.name).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Serve files from `dir` over HTTP on a loopback port
+    fn serve(dir: PathBuf) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(&stream);
+                let mut request = String::new();
+                reader.read_line(&mut request).unwrap();
+                // Drain the headers so closing the connection does not reset it
+                let mut header = String::new();
+                while reader.read_line(&mut header).is_ok_and(|read| read > 2) {
+                    header.clear();
+                }
+                let path = request.split_whitespace().nth(1).unwrap_or("/").trim_start_matches('/');
+                let mut stream = &stream;
+                match fs::read(dir.join(path)) {
+                    Ok(body) => {
+                        write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", body.len()).unwrap();
+                        stream.write_all(&body).unwrap();
+                    }
+                    Err(_) => write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n").unwrap(),
+                }
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[test]
+    fn test_asset_urls_and_checksums() {
+        let spec = ReleaseSpec::new(
+            "starship",
+            "1.21.1",
+            "https://github.com/starship/starship/releases/download/v{version}/starship-{arch}-{os}.tar.gz",
+            Checksum::Sidecar(".sha256".to_string()),
+        )
+        .with_os_name("linux", "unknown-linux-musl")
+        .with_os_name("darwin", "apple-darwin")
+        .with_arch_name("amd64", "x86_64")
+        .with_arch_name("arm64", "aarch64");
+        assert_eq!(
+            spec.checksum_url("darwin", "arm64"),
+            "https://github.com/starship/starship/releases/download/v1.21.1/starship-aarch64-apple-darwin.tar.gz.sha256"
+        );
+
+        let list = "1111  tool-linux-amd64.tar.gz\n2222 *dist/tool-darwin-arm64.zip\n";
+        assert_eq!(checksum_for(list, "tool-darwin-arm64.zip").as_deref(), Some("2222"));
+        assert_eq!(checksum_for(list, "tool-linux-arm64.tar.gz"), None);
+        assert_eq!(checksum_for("3333\n", "anything").as_deref(), Some("3333"));
+    }
+
+    #[test]
+    fn test_install_from_fixture_server() {
+        let fixtures = tempfile::tempdir().unwrap();
+        let release = fixtures.path().join("v2");
+        fs::create_dir_all(release.join("tool-2")).unwrap();
+        fs::write(release.join("tool-2/tool"), "#!/bin/sh\necho 2\n").unwrap();
+        let archive = release.join("tool-linux-amd64.tar.gz");
+        run_cmd("tar", &["-czf", archive.to_str().unwrap(), "-C", release.to_str().unwrap(), "tool-2"]).unwrap();
+        let digest = download::sha256_file(&archive).unwrap();
+        fs::write(release.join("checksums.txt"), format!("{}  tool-linux-amd64.tar.gz\n", digest)).unwrap();
+        fs::write(release.join("bad.txt"), format!("{}  tool-linux-amd64.tar.gz\n", "0".repeat(64))).unwrap();
+
+        let server = serve(fixtures.path().to_path_buf());
+        let home = tempfile::tempdir().unwrap();
+        let installer = ReleaseInstaller::with_dirs(home.path().join("bin"), home.path().join("releases.json"));
+        let spec = |version: &str, checksums: &str| {
+            ReleaseSpec::new(
+                "tool",
+                version,
+                format!("{}/v{{version}}/tool-{{os}}-{{arch}}.tar.gz", server),
+                Checksum::List(format!("{}/v{{version}}/{}", server, checksums)),
+            )
+        };
+
+        // A mismatched checksum leaves nothing behind
+        assert!(installer.install(&spec("2", "bad.txt"), "linux", "amd64").is_err());
+        assert!(!installer.binary_path(&spec("2", "bad.txt")).exists());
+
+        let current = spec("2", "checksums.txt");
+        installer.install(&current, "linux", "amd64").unwrap();
+        let binary = installer.binary_path(&current);
+        assert_eq!(fs::read_to_string(&binary).unwrap(), "#!/bin/sh\necho 2\n");
+        assert_eq!(fs::metadata(&binary).unwrap().permissions().mode() & 0o777, 0o755);
+        assert_eq!(installer.installed_version("tool").as_deref(), Some("2"));
+        assert!(installer.is_current(&current));
+        assert!(!installer.is_current(&spec("3", "checksums.txt")));
+
+        installer.uninstall(&current).unwrap();
+        assert!(!binary.exists());
+        assert_eq!(installer.installed_version("tool"), None);
+    }
+
+    #[test]
+    fn test_parallel_version_updates_are_all_kept() {
+        let home = tempfile::tempdir().unwrap();
+        let state_file = home.path().join("releases.json");
+        let handles: Vec<_> = (0..8)
+            .map(|index| {
+                let installer = ReleaseInstaller::with_dirs(home.path().join("bin"), state_file.clone());
+                thread::spawn(move || {
+                    installer
+                        .update_versions(|versions| {
+                            versions.insert(format!("tool-{}", index), "1".to_string());
+                            true
+                        })
+                        .unwrap()
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let installer = ReleaseInstaller::with_dirs(home.path().join("bin"), state_file);
+        assert_eq!(installer.versions().len(), 8);
+    }
+}
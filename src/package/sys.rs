@@ -1,9 +1,8 @@
 use anyhow::{anyhow, Result};
 use dirs::home_dir;
 use std::fs;
+use std::path::PathBuf;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use std::{path::PathBuf, process::Command};
-use which::which;
 
 pub const RETRY_ATTEMPTS: u32 = 3;
 pub const CACHE_DURATION_SECS: u64 = 24 * 60 * 60; // 24 hours
@@ -48,6 +47,24 @@ impl PackageCache {
         now.as_secs() - modified.as_secs() < CACHE_DURATION_SECS
     }
 
+    /// Cache entries older than `CACHE_DURATION_SECS`
+    pub fn stale_entries(&self) -> Result<Vec<PathBuf>> {
+        let mut stale = Vec::new();
+        for entry in fs::read_dir(&self.cache_dir)? {
+            let path = entry?.path();
+            let age = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .unwrap_or_default();
+            if age.as_secs() >= CACHE_DURATION_SECS {
+                stale.push(path);
+            }
+        }
+        stale.sort();
+        Ok(stale)
+    }
+
     pub fn update(&self, os: &str, package: &str) -> Result<()> {
         let cache_path = self.get_cache_path(os, package);
         fs::write(&cache_path, "")?;
@@ -77,36 +94,6 @@ where
     }
 }
 
-/// Ensure sudo access is available and cached
-pub fn ensure_sudo_access() -> Result<()> {
-    // Check if sudo is available
-    which("sudo").map_err(|_| anyhow!("sudo is not available"))?;
-
-    // Try to get cached sudo credentials
-    let output = Command::new("sudo")
-        .arg("-n")
-        .arg("true")
-        .output()
-        .map_err(|e| anyhow!("Failed to execute sudo: {}", e))?;
-
-    if !output.status.success() {
-        // Need to prompt for password
-        println!("⚡ Sudo access required for system setup");
-        println!("🔐 Please enter your password when prompted");
-
-        let status = Command::new("sudo")
-            .arg("true")
-            .status()
-            .map_err(|e| anyhow!("Failed to execute sudo: {}", e))?;
-
-        if !status.success() {
-            return Err(anyhow!("Failed to obtain sudo access"));
-        }
-    }
-
-    Ok(())
-}
-
 /// Detect the current platform (OS and architecture)
 pub fn detect_platform() -> Result<(String, String)> {
     let os = std::env::consts::OS;
@@ -0,0 +1,336 @@
+use dirs::home_dir;
+use std::fs;
+use std::path::Path;
+use which::which;
+
+use crate::inventory;
+use crate::menu::{InstallPlan, Toolchain};
+use crate::package::{manager::PackageManager, sys, Privilege};
+use crate::steps;
+
+/// Headroom on top of the component estimates, in MiB
+const DISK_MARGIN_MB: u64 = 1024;
+
+/// Processes that hold the package database lock while they run
+const PACKAGE_PROCESSES: &[(PackageManager, &[&str])] = &[
+    (PackageManager::Apt, &["apt", "apt-get", "aptitude", "dpkg", "unattended-upgr"]),
+    (PackageManager::Yum, &["yum", "dnf", "rpm"]),
+    (PackageManager::Brew, &["brew"]),
+];
+
+/// Result of a single preflight check
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    Passed(String),
+    Failed { problem: String, hint: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+}
+
+impl Check {
+    pub fn passed(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, status: Status::Passed(detail.into()) }
+    }
+
+    pub fn failed(name: &'static str, problem: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Failed { problem: problem.into(), hint: hint.into() },
+        }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(self.status, Status::Failed { .. })
+    }
+}
+
+/// Whether the plan installs system packages at all
+///
+/// Toolchains count too, since they install their own base packages.
+fn uses_package_manager(plan: &InstallPlan) -> bool {
+    plan.installs_system_packages()
+        && plan.components.iter().any(|component| {
+            !component.dependencies_for(plan.accelerator).is_empty() || !component.toolchains.is_empty()
+        })
+}
+
+/// Whether the plan installs anything that needs root
+pub fn needs_root(plan: &InstallPlan) -> bool {
+    !cfg!(target_os = "macos") && uses_package_manager(plan)
+}
+
+/// Check everything that would otherwise fail deep into the run
+///
+/// The sudo check runs last because it may prompt for a password.
+pub fn run(plan: &InstallPlan) -> Vec<Check> {
+    let mut checks = vec![check_architecture()];
+
+    let home = home_dir();
+    match &home {
+        Some(home) => {
+            checks.push(check_home_writable(home));
+            checks.push(check_disk_space(plan, home));
+        }
+        None => checks.push(Check::failed(
+            "Home directory",
+            "Could not determine the home directory",
+            "Set $HOME and run setcyrup again",
+        )),
+    }
+
+    // User-only runs work without one, e.g. on Alpine or without sudo
+    if uses_package_manager(plan) {
+        let package_manager = PackageManager::detect();
+        checks.push(match &package_manager {
+            Ok(pm) => Check::passed("Package manager", pm.to_string()),
+            Err(e) => Check::failed(
+                "Package manager",
+                e.to_string(),
+                "setcyrup supports apt-get, yum and Homebrew, or pass --user-only to skip system packages",
+            ),
+        });
+        if let Ok(pm) = package_manager {
+            checks.push(check_package_lock(Path::new("/"), pm));
+        }
+    }
+
+    checks.push(check_tools(plan));
+    if plan.components.iter().any(|c| c.toolchains.contains(&Toolchain::Rust)) {
+        checks.push(check_cargo_crates());
+    }
+
+    if needs_root(plan) {
+        checks.push(check_privilege(plan.privilege));
+    } else if !plan.installs_system_packages() {
+        checks.push(Check::passed("Administrator access", "not needed in user-only mode"));
+    }
+
+    checks
+}
+
+/// Print the checks as a checklist, with a hint under each failure
+pub fn print(title: &str, checks: &[Check]) {
+    println!("\n{}:", title);
+    for check in checks {
+        match &check.status {
+            Status::Passed(detail) => println!("  ✔ {}: {}", check.name, detail),
+            Status::Failed { problem, hint } => {
+                println!("  ✘ {}: {}", check.name, problem);
+                println!("      → {}", hint);
+            }
+        }
+    }
+    println!();
+}
+
+fn check_architecture() -> Check {
+    match sys::detect_platform() {
+        Ok((os, arch)) => Check::passed("Platform", format!("{}/{}", os, arch)),
+        Err(e) => Check::failed(
+            "Platform",
+            e.to_string(),
+            "setcyrup supports x86_64 and aarch64 on Linux and macOS",
+        ),
+    }
+}
+
+fn check_home_writable(home: &Path) -> Check {
+    let probe = home.join(format!(".setcyrup-preflight-{}", std::process::id()));
+    match fs::write(&probe, "").and_then(|_| fs::remove_file(&probe)) {
+        Ok(()) => Check::passed("Home directory", format!("{} is writable", home.display())),
+        Err(e) => Check::failed(
+            "Home directory",
+            format!("Cannot write to {}: {}", home.display(), e),
+            format!("Fix the ownership with `sudo chown -R $USER {}`", home.display()),
+        ),
+    }
+}
+
+fn check_disk_space(plan: &InstallPlan, home: &Path) -> Check {
+    let needed_mb = plan.components.iter().map(|c| c.disk_mb).sum::<u64>() + DISK_MARGIN_MB;
+    match inventory::disk_usage(home) {
+        Ok(disk) => disk_space_status(disk.free_bytes / (1 << 20), needed_mb),
+        Err(e) => Check::failed(
+            "Free disk space",
+            e.to_string(),
+            "Make sure the home directory is on a mounted filesystem",
+        ),
+    }
+}
+
+fn disk_space_status(free_mb: u64, needed_mb: u64) -> Check {
+    let gib = |mb: u64| format!("{:.1} GiB", mb as f64 / 1024.0);
+    if free_mb >= needed_mb {
+        Check::passed("Free disk space", format!("{} free, about {} needed", gib(free_mb), gib(needed_mb)))
+    } else {
+        Check::failed(
+            "Free disk space",
+            format!("{} free, about {} needed", gib(free_mb), gib(needed_mb)),
+            "Free up space in your home directory or deselect some components",
+        )
+    }
+}
+
+/// Fail when another process is using the package database
+fn check_package_lock(root: &Path, pm: PackageManager) -> Check {
+    let names = PACKAGE_PROCESSES
+        .iter()
+        .find(|(candidate, _)| *candidate == pm)
+        .map_or(&[][..], |(_, names)| names);
+
+    match running_processes(root, names).first() {
+        None => Check::passed("Package database", "not locked"),
+        Some((pid, name)) => Check::failed(
+            "Package database",
+            format!("{} (pid {}) is using the {} database", name, pid, pm),
+            "Wait for it to finish; automatic updates usually take a few minutes",
+        ),
+    }
+}
+
+/// Processes under `root/proc` whose command name is one of `names`
+fn running_processes(root: &Path, names: &[&str]) -> Vec<(u32, String)> {
+    let entries = match fs::read_dir(root.join("proc")) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut found: Vec<(u32, String)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let comm = fs::read_to_string(entry.path().join("comm")).ok()?;
+            let comm = comm.trim();
+            names.contains(&comm).then(|| (pid, comm.to_string()))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+/// Tools the plan's installers run before any package is installed
+fn required_tools(plan: &InstallPlan) -> Vec<&'static str> {
+    // Toolchain installers are fetched with curl
+    let mut required = vec![];
+    if plan.components.iter().any(|c| !c.toolchains.is_empty()) {
+        required.push("curl");
+    }
+    // The fnm installer unpacks a zip release
+    if plan.components.iter().any(|c| c.toolchains.contains(&Toolchain::Node)) {
+        required.push("unzip");
+    }
+    required
+}
+
+fn check_cargo_crates() -> Check {
+    match steps::cargo::configured_crates() {
+        Ok(crates) => Check::passed("Cargo crates", format!("{} to install", crates.len())),
+        Err(e) => Check::failed(
+            "Cargo crates",
+            format!("{:#}", e),
+            "Fix or unset CYRUP_CARGO_CRATES; entries look like `name@0.6` or `name?git=<url>&rev=<commit>`",
+        ),
+    }
+}
+
+fn check_tools(plan: &InstallPlan) -> Check {
+    let required = required_tools(plan);
+    let missing: Vec<&str> = required.iter().copied().filter(|tool| which(tool).is_err()).collect();
+    if missing.is_empty() {
+        Check::passed("Required tools", "all present")
+    } else {
+        Check::failed(
+            "Required tools",
+            format!("Missing {}", missing.join(", ")),
+            format!("Install them with your package manager, e.g. `sudo apt-get install {}`", missing.join(" ")),
+        )
+    }
+}
+
+fn check_privilege(privilege: Privilege) -> Check {
+    if privilege == Privilege::Root {
+        return Check::passed("Administrator access", "running as root");
+    }
+    match privilege.ensure_access() {
+        Ok(()) => Check::passed("Administrator access", format!("{} credentials cached", privilege)),
+        Err(e) => Check::failed(
+            "Administrator access",
+            e.to_string(),
+            "Add your user to the sudo or doas configuration, or pass --user-only to skip system packages",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::Accelerator;
+    use crate::menu::InstallModule;
+    use crate::steps::rust::RustSpec;
+
+    #[test]
+    fn test_plan_requirements() {
+        let mut plan = InstallPlan {
+            target: None,
+            components: vec![InstallModule {
+                name: "Cyrup Developer".to_string(),
+                description: String::new(),
+                dependencies: vec![],
+                variants: vec![],
+                toolchains: vec![Toolchain::Rust],
+                services: vec![],
+                disk_mb: 0,
+                commands: vec![],
+            }],
+            accelerator: Accelerator::Cpu,
+            pytorch_index: None,
+            privilege: Privilege::Sudo,
+            rust: RustSpec::default(),
+        };
+        assert!(uses_package_manager(&plan));
+
+        assert_eq!(required_tools(&plan), vec!["curl"]);
+        plan.components[0].toolchains.push(Toolchain::Node);
+        assert_eq!(required_tools(&plan), vec!["curl", "unzip"]);
+
+        plan.privilege = Privilege::UserOnly;
+        assert!(!uses_package_manager(&plan));
+        assert!(!run(&plan).iter().any(|check| check.name == "Package manager"));
+    }
+
+    #[test]
+    fn test_disk_space_status() {
+        assert!(!disk_space_status(20 * 1024, 5000).is_failed());
+
+        let check = disk_space_status(2048, 13 * 1024);
+        assert_eq!(
+            check.status,
+            Status::Failed {
+                problem: "2.0 GiB free, about 13.0 GiB needed".to_string(),
+                hint: "Free up space in your home directory or deselect some components".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_package_lock_detects_running_dpkg() {
+        let root = tempfile::tempdir().unwrap();
+        for (pid, comm) in [("1", "systemd"), ("4242", "dpkg"), ("self", "setcyrup")] {
+            let dir = root.path().join("proc").join(pid);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("comm"), format!("{}\n", comm)).unwrap();
+        }
+
+        let check = check_package_lock(root.path(), PackageManager::Apt);
+        assert!(check.is_failed());
+        assert!(matches!(
+            check.status,
+            Status::Failed { ref problem, .. } if problem.starts_with("dpkg (pid 4242)")
+        ));
+        assert!(!check_package_lock(root.path(), PackageManager::Yum).is_failed());
+    }
+}
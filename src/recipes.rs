@@ -63,7 +63,7 @@ impl Recipe {
             }
 
             // Check for consistent package manager types
-            let expected_type = packages[0].manager_type.clone();
+            let expected_type = packages[0].manager_type;
             for package in packages {
                 if package.manager_type != expected_type {
                     return Err(anyhow!(
@@ -131,7 +131,7 @@ impl DevToolsRecipe {
                 return Err(anyhow!("Empty package list for platform: {}", platform));
             }
 
-            let expected_type = packages[0].manager_type.clone();
+            let expected_type = packages[0].manager_type;
             for package in packages {
                 if package.manager_type != expected_type {
                     return Err(anyhow!(
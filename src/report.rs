@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Outcome of a single installation step
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepStatus {
+    /// Step ran and completed successfully
+    Success,
+    /// Step ran and failed with the given error
+    Failed(String),
+    /// Step did not run, with the reason why
+    Skipped(String),
+}
+
+/// Result of a single step, as shown on the summary screen
+#[derive(Debug, Clone)]
+pub struct StepResult {
+    /// Component the step belongs to
+    pub component: String,
+    /// Human readable step name
+    pub name: String,
+    /// How the step ended
+    pub status: StepStatus,
+    /// Time spent running the step
+    pub duration: Duration,
+    /// Extra information from the step, e.g. how a crate was installed
+    pub detail: Option<String>,
+}
+
+/// Collected results of an installation run
+#[derive(Debug)]
+pub struct InstallReport {
+    started: Instant,
+    /// Per-step results in execution order
+    pub steps: Vec<StepResult>,
+    /// Non-fatal problems the user should know about
+    pub warnings: Vec<String>,
+    /// Actions the user must take after installation
+    pub follow_ups: Vec<String>,
+    /// Location of the full log for this run
+    pub log_path: Option<PathBuf>,
+}
+
+impl InstallReport {
+    /// Create an empty report, starting the run clock
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            steps: Vec::new(),
+            warnings: Vec::new(),
+            follow_ups: Vec::new(),
+            log_path: None,
+        }
+    }
+
+    /// Record the outcome of a step
+    pub fn record(
+        &mut self,
+        component: impl Into<String>,
+        name: impl Into<String>,
+        status: StepStatus,
+        duration: Duration,
+    ) -> &mut StepResult {
+        self.steps.push(StepResult {
+            component: component.into(),
+            name: name.into(),
+            status,
+            duration,
+            detail: None,
+        });
+        self.steps.last_mut().expect("step was just pushed")
+    }
+
+    /// Add a warning to show on the summary screen
+    pub fn warn(&mut self, warning: impl Into<String>) {
+        self.warnings.push(warning.into());
+    }
+
+    /// Add a follow-up action, ignoring duplicates
+    pub fn follow_up(&mut self, action: impl Into<String>) {
+        let action = action.into();
+        if !self.follow_ups.contains(&action) {
+            self.follow_ups.push(action);
+        }
+    }
+
+    /// Whether any step failed
+    pub fn has_failures(&self) -> bool {
+        self.steps
+            .iter()
+            .any(|step| matches!(step.status, StepStatus::Failed(_)))
+    }
+
+    /// Total wall-clock time since the report was created
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+impl Default for InstallReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Format a duration for display, e.g. "850ms", "4.2s" or "3m 07s"
+pub fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis < 1000 {
+        format!("{}ms", millis)
+    } else if millis < 60_000 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        let secs = duration.as_secs();
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_failures_and_follow_ups() {
+        let mut report = InstallReport::new();
+        report.record("Setup", "Configure shell", StepStatus::Success, Duration::ZERO);
+        assert!(!report.has_failures());
+
+        report.record(
+            "Setup",
+            "Detect GPU",
+            StepStatus::Failed("boom".to_string()),
+            Duration::ZERO,
+        );
+        assert!(report.has_failures());
+
+        report.follow_up("Restart your terminal");
+        report.follow_up("Restart your terminal");
+        assert_eq!(report.follow_ups.len(), 1);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(850)), "850ms");
+        assert_eq!(format_duration(Duration::from_millis(4200)), "4.2s");
+        assert_eq!(format_duration(Duration::from_secs(187)), "3m 07s");
+    }
+}
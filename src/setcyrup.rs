@@ -1,83 +1,199 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use crate::checkpoint::CheckpointStore;
+use crate::executor::{Event, Executor};
+use crate::logging;
+use crate::menu::InstallPlan;
+use crate::report::{InstallReport, StepStatus};
+use crate::steps::{self, Step};
 use crate::ui::Installer;
-use crate::system;
 use dirs::home_dir;
 use std::fs;
-use std::path::PathBuf;
-
-const RETRY_ATTEMPTS: u32 = 3;
-
-pub async fn run_installer(installer: &mut Installer) -> Result<()> {
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+pub async fn run_installer(
+    installer: &mut Installer,
+    plan: &InstallPlan,
+    report: &mut InstallReport,
+    checkpoints: CheckpointStore,
+    jobs: usize,
+) -> Result<()> {
     installer.update_status("Starting installation...")?;
-    installer.update_progress(0.1)?;
-
-    // Create necessary directories
-    let home = home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
-    let config_dir = home.join(".config/cyrup");
-    fs::create_dir_all(&config_dir)?;
-
-    installer.update_status("Configuring shell...")?;
-    installer.update_progress(0.3)?;
+    installer.update_progress(0.0)?;
+
+    let steps: Vec<Arc<dyn Step>> = steps::for_plan(plan).into_iter().map(Arc::from).collect();
+    let total = steps.len();
+    let mut finished = 0;
+    let mut failures = 0;
+
+    let outcomes = Executor::new(jobs)
+        .run(steps.clone(), Arc::new(Mutex::new(checkpoints)), |event| {
+            match event {
+                Event::Started(step) => installer.start_task(step.id(), step.component(), step.name()),
+                Event::Finished(step, outcome) => {
+                    finished += 1;
+                    if matches!(outcome.status, StepStatus::Failed(_)) {
+                        failures += 1;
+                    }
+                    installer.finish_task(&step.id());
+                    installer.update_status(format!(
+                        "{} of {} steps finished, {} failed",
+                        finished, total, failures
+                    ))?;
+                    installer.update_progress(finished as f64 / total as f64)
+                }
+                Event::Tick => installer.redraw(),
+            }
+        })
+        .await?;
+
+    // Record in plan order so the summary groups steps by component
+    let mut failed = Vec::new();
+    for (step, outcome) in steps.iter().zip(outcomes) {
+        if matches!(outcome.status, StepStatus::Failed(_)) {
+            failed.push(step.name());
+        }
+        report.record(step.component(), step.name(), outcome.status, outcome.duration).detail =
+            outcome.notes.detail;
+        for warning in outcome.notes.warnings {
+            report.warn(warning);
+        }
+        for action in outcome.notes.follow_ups {
+            report.follow_up(action);
+        }
+    }
 
-    // Configure shell
-    configure_shell()?;
+    if !failed.is_empty() {
+        return Err(anyhow!("{} step(s) failed: {}", failed.len(), failed.join(", ")));
+    }
 
     installer.update_status("Installation complete!")?;
-    installer.update_progress(1.0)?;
+    Ok(())
+}
+
+/// Undo the steps of `plan` in reverse order, printing each outcome
+///
+/// Steps whose work is not present are skipped, and steps that do not
+/// support removal are kept.
+pub fn run_uninstaller(plan: &InstallPlan, checkpoints: &mut CheckpointStore) -> Result<()> {
+    let mut failed = Vec::new();
+    for step in steps::for_plan(plan).iter().rev() {
+        if !step.check().unwrap_or(false) {
+            continue;
+        }
+        match step.uninstall() {
+            Ok(true) => {
+                checkpoints.invalidate(&step.id())?;
+                logging::info(format!("Uninstalled {}", step.id()));
+                println!("  ✔ Removed: {}", step.name());
+            }
+            Ok(false) => println!("  - Kept: {}", step.name()),
+            Err(e) => {
+                logging::error(format!("Failed to uninstall {}: {:#}", step.id(), e));
+                println!("  ✘ {}: {:#}", step.name(), e);
+                failed.push(step.name());
+            }
+        }
+    }
 
+    if !failed.is_empty() {
+        return Err(anyhow!("{} step(s) could not be uninstalled: {}", failed.len(), failed.join(", ")));
+    }
     Ok(())
 }
 
-fn configure_shell() -> Result<()> {
+/// User rc file, Cyrup config file name and its contents, per shell
+pub(crate) const SHELL_CONFIGS: &[(&str, &str, &str)] = &[
+    (
+        ".zshrc",
+        "zshrc",
+        "# Cyrup zsh configuration\n\
+         export PATH=\"$HOME/.cargo/bin:$PATH\"\n\
+         \n\
+         # Node.js through fnm\n\
+         if [ -x \"$HOME/.local/share/fnm/fnm\" ]; then\n\
+         \x20 export PATH=\"$HOME/.local/share/fnm:$PATH\"\n\
+         \x20 eval \"$(fnm env --use-on-cd --shell zsh)\"\n\
+         fi\n\
+         \n\
+         # Go from the official tarball\n\
+         if [ -x \"$HOME/.local/share/go/bin/go\" ]; then\n\
+         \x20 export GOROOT=\"$HOME/.local/share/go\"\n\
+         \x20 export GOPATH=\"${GOPATH:-$HOME/go}\"\n\
+         \x20 export PATH=\"$GOROOT/bin:$GOPATH/bin:$PATH\"\n\
+         fi\n",
+    ),
+    (
+        ".bashrc",
+        "bashrc",
+        "# Cyrup bash configuration\n\
+         export PATH=\"$HOME/.cargo/bin:$PATH\"\n\
+         \n\
+         # Node.js through fnm\n\
+         if [ -x \"$HOME/.local/share/fnm/fnm\" ]; then\n\
+         \x20 export PATH=\"$HOME/.local/share/fnm:$PATH\"\n\
+         \x20 eval \"$(fnm env --use-on-cd --shell bash)\"\n\
+         fi\n\
+         \n\
+         # Go from the official tarball\n\
+         if [ -x \"$HOME/.local/share/go/bin/go\" ]; then\n\
+         \x20 export GOROOT=\"$HOME/.local/share/go\"\n\
+         \x20 export GOPATH=\"${GOPATH:-$HOME/go}\"\n\
+         \x20 export PATH=\"$GOROOT/bin:$GOPATH/bin:$PATH\"\n\
+         fi\n",
+    ),
+];
+
+fn shell_dir() -> Result<PathBuf> {
     let home = home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
-    let config_dir = home.join(".config/cyrup");
-
-    // Create shell config files
-    let shell_dir = config_dir.join("shell");
-    fs::create_dir_all(&shell_dir)?;
+    Ok(home.join(".config/cyrup/shell"))
+}
 
-    // Write shell configs
-    write_shell_config(&shell_dir)?;
+pub(crate) fn source_line(shell_dir: &Path, config: &str) -> String {
+    format!("\n# Cyrup\nsource {}/{}\n", shell_dir.display(), config)
+}
 
-    // Update user's shell config files
-    update_user_shell_configs(&shell_dir)?;
+pub(crate) fn configure_shell() -> Result<()> {
+    let home = home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
 
-    Ok(())
-}
+    // Create shell config files
+    let shell_dir = shell_dir()?;
+    fs::create_dir_all(&shell_dir)?;
 
-fn write_shell_config(shell_dir: &PathBuf) -> Result<()> {
-    // Write zshrc
-    let zshrc = shell_dir.join("zshrc");
-    fs::write(&zshrc, "# Cyrup zsh configuration\nexport PATH=\"$HOME/.cargo/bin:$PATH\"\n")?;
+    for (rc, config, contents) in SHELL_CONFIGS {
+        // Write shell config
+        fs::write(shell_dir.join(config), contents)?;
 
-    // Write bashrc
-    let bashrc = shell_dir.join("bashrc");
-    fs::write(&bashrc, "# Cyrup bash configuration\nexport PATH=\"$HOME/.cargo/bin:$PATH\"\n")?;
+        // Source it from the user's rc file if they have one
+        let user_rc = home.join(rc);
+        if user_rc.exists() {
+            append_to_file(&user_rc, &source_line(&shell_dir, config))?;
+        }
+    }
 
     Ok(())
 }
 
-fn update_user_shell_configs(shell_dir: &PathBuf) -> Result<()> {
+/// Whether `configure_shell` has nothing left to do
+pub(crate) fn shell_configured() -> Result<bool> {
     let home = home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
+    let shell_dir = shell_dir()?;
 
-    // Update .zshrc if it exists
-    let user_zshrc = home.join(".zshrc");
-    if user_zshrc.exists() {
-        let source_line = format!("\n# Cyrup\nsource {}/zshrc\n", shell_dir.display());
-        append_to_file(&user_zshrc, &source_line)?;
-    }
+    for (rc, config, contents) in SHELL_CONFIGS {
+        if fs::read_to_string(shell_dir.join(config)).ok().as_deref() != Some(*contents) {
+            return Ok(false);
+        }
 
-    // Update .bashrc if it exists
-    let user_bashrc = home.join(".bashrc");
-    if user_bashrc.exists() {
-        let source_line = format!("\n# Cyrup\nsource {}/bashrc\n", shell_dir.display());
-        append_to_file(&user_bashrc, &source_line)?;
+        let user_rc = home.join(rc);
+        if user_rc.exists() && !fs::read_to_string(&user_rc)?.contains(&source_line(&shell_dir, config)) {
+            return Ok(false);
+        }
     }
 
-    Ok(())
+    Ok(true)
 }
 
-fn append_to_file(file: &PathBuf, content: &str) -> Result<()> {
+fn append_to_file(file: &Path, content: &str) -> Result<()> {
     let current_content = fs::read_to_string(file)?;
     if !current_content.contains(content) {
         fs::write(file, format!("{}{}", current_content, content))?;
@@ -0,0 +1,320 @@
+use anyhow::{anyhow, Context, Result};
+use std::env;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use super::rust::{cargo_bin_dir, rustup_bin, COMPONENT};
+use super::{Lock, Step, StepNotes};
+use crate::logging;
+use crate::package::cargo::{parse_install_list, CrateSpec};
+use crate::package::catalog;
+use crate::report::format_duration;
+use crate::system::run_cmd;
+
+/// Base URL of a mirror holding `<name>-<version>-<target>.tgz` archives
+const MIRROR_VAR: &str = "CYRUP_CARGO_MIRROR";
+
+/// Comma separated `CrateSpec::parse` entries replacing the catalog's crates
+const CRATES_VAR: &str = "CYRUP_CARGO_CRATES";
+
+pub(crate) const BINSTALL_RELEASE_URL: &str = "https://github.com/cargo-bins/cargo-binstall/releases/latest/download";
+
+/// Rough source build times on an 8 core machine, for the time saved
+const SOURCE_BUILD_ESTIMATES: &[(&str, u64)] = &[
+    ("dioxus-cli", 900),
+    ("hurl", 420),
+    ("pueue", 240),
+    ("zellij", 600),
+    ("zoxide", 60),
+    ("wasm-pack", 240),
+    ("taplo-cli", 240),
+    ("cargo-watch", 180),
+    ("cargo-edit", 240),
+    ("cargo-update", 180),
+];
+
+/// Estimate for crates missing from the table
+const DEFAULT_BUILD_SECS: u64 = 120;
+
+/// Crates for `cargo install`, from `CYRUP_CARGO_CRATES` if set
+pub(crate) fn configured_crates() -> Result<Vec<CrateSpec>> {
+    crates(env::var(CRATES_VAR).ok().as_deref())
+}
+
+fn crates(configured: Option<&str>) -> Result<Vec<CrateSpec>> {
+    match configured {
+        Some(list) => list
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(CrateSpec::parse)
+            .collect::<Result<_>>()
+            .with_context(|| format!("Invalid {}", CRATES_VAR)),
+        None => Ok(catalog::get_cargo_packages()),
+    }
+}
+
+/// Where a crate's binaries came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InstallMethod {
+    /// Prebuilt archive from `CYRUP_CARGO_MIRROR`
+    Mirror,
+    /// Prebuilt binary found through the crate's binstall metadata
+    Release,
+    /// Compiled with `cargo install`
+    Source,
+}
+
+impl InstallMethod {
+    /// Summary line for a crate installed this way in `elapsed`
+    fn describe(&self, name: &str, elapsed: Duration) -> String {
+        let estimate = Duration::from_secs(
+            SOURCE_BUILD_ESTIMATES
+                .iter()
+                .find(|(candidate, _)| *candidate == name)
+                .map_or(DEFAULT_BUILD_SECS, |(_, secs)| *secs),
+        );
+        let source = match self {
+            InstallMethod::Mirror => "prebuilt from mirror",
+            InstallMethod::Release => "prebuilt release",
+            InstallMethod::Source => return "built from source".to_string(),
+        };
+        match estimate.checked_sub(elapsed) {
+            Some(saved) if !saved.is_zero() => format!("{}, saved about {}", source, format_duration(saved)),
+            _ => source.to_string(),
+        }
+    }
+}
+
+/// Arguments for `cargo binstall` fetching `name` with `method`
+///
+/// `name` may carry a version requirement, e.g. `dioxus-cli@0.6`.
+///
+/// Compiling is left to the source fallback, so binstall never falls
+/// back to a build of its own.
+pub(crate) fn binstall_args(name: &str, method: InstallMethod, mirror: Option<&str>) -> Vec<String> {
+    let mut args: Vec<String> = ["--no-confirm", "--disable-strategies", "quick-install,compile"]
+        .map(String::from)
+        .to_vec();
+    if let (InstallMethod::Mirror, Some(mirror)) = (method, mirror) {
+        args.push("--pkg-url".to_string());
+        args.push(format!("{}/{{ name }}-{{ version }}-{{ target }}.tgz", mirror.trim_end_matches('/')));
+        args.push("--pkg-fmt".to_string());
+        args.push("tgz".to_string());
+    }
+    args.push(name.to_string());
+    args
+}
+
+/// Prebuilt sources to try, in order
+fn prebuilt_methods(mirror: Option<&str>) -> Vec<InstallMethod> {
+    let mut methods = Vec::new();
+    if mirror.is_some() {
+        methods.push(InstallMethod::Mirror);
+    }
+    methods.push(InstallMethod::Release);
+    methods
+}
+
+fn binstall_path() -> Option<String> {
+    let path = cargo_bin_dir().ok()?.join("cargo-binstall");
+    path.exists().then(|| path.display().to_string())
+}
+
+/// Target triple of the cargo-binstall release for this machine
+fn binstall_target() -> Result<&'static str> {
+    match (env::consts::OS, env::consts::ARCH) {
+        ("linux", "x86_64") => Ok("x86_64-unknown-linux-musl"),
+        ("linux", "aarch64") => Ok("aarch64-unknown-linux-musl"),
+        ("macos", "x86_64") => Ok("x86_64-apple-darwin"),
+        ("macos", "aarch64") => Ok("aarch64-apple-darwin"),
+        (os, arch) => Err(anyhow!("No cargo-binstall release for {}/{}", os, arch)),
+    }
+}
+
+fn download_binstall() -> Result<()> {
+    let target = binstall_target()?;
+    let archive = if cfg!(target_os = "macos") { "zip" } else { "tgz" };
+    let url = format!("{}/cargo-binstall-{}.{}", BINSTALL_RELEASE_URL, target, archive);
+    let download = env::temp_dir().join(format!("cargo-binstall.{}", archive));
+    let download = download.to_str().ok_or_else(|| anyhow!("Invalid temp path"))?;
+    let bin_dir = cargo_bin_dir()?;
+    let bin_dir = bin_dir.to_str().ok_or_else(|| anyhow!("Invalid cargo bin path"))?;
+
+    run_cmd("curl", &["--proto", "=https", "--tlsv1.2", "-fsSL", &url, "-o", download])
+        .context("Failed to download cargo-binstall")?;
+    // bsdtar on macOS also reads zip archives
+    run_cmd("tar", &["-xf", download, "-C", bin_dir]).context("Failed to unpack cargo-binstall")
+}
+
+/// Download cargo-binstall into the cargo bin directory
+///
+/// Optional: without it every crate is simply built from source.
+pub struct CargoBinstall;
+
+impl Step for CargoBinstall {
+    fn id(&self) -> String {
+        "rust.cargo-binstall".to_string()
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        "Install cargo-binstall".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["rust.rustup".to_string()]
+    }
+
+    fn check(&self) -> Result<bool> {
+        Ok(binstall_path().is_some())
+    }
+
+    fn apply(&self, notes: &mut StepNotes) -> Result<()> {
+        if let Err(e) = download_binstall() {
+            let warning = format!("cargo-binstall unavailable, crates will be built from source: {:#}", e);
+            logging::warn(&warning);
+            notes.warnings.push(warning);
+        }
+        Ok(())
+    }
+
+    fn verify(&self) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+/// Install a catalog crate, preferring prebuilt binaries
+pub struct CargoCrate {
+    pub spec: CrateSpec,
+    /// System package steps to wait for, since source builds may need
+    /// their headers, e.g. libssl-dev
+    pub package_steps: Vec<String>,
+}
+
+impl CargoCrate {
+    /// Try each prebuilt source through cargo-binstall
+    fn install_prebuilt(&self) -> Option<InstallMethod> {
+        let name = self.spec.binstall_name()?;
+        let binstall = binstall_path()?;
+        let mirror = env::var(MIRROR_VAR).ok().filter(|mirror| !mirror.is_empty());
+
+        prebuilt_methods(mirror.as_deref()).into_iter().find(|method| {
+            let args = binstall_args(&name, *method, mirror.as_deref());
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            match run_cmd(&binstall, &args) {
+                Ok(()) => true,
+                Err(e) => {
+                    logging::info(format!("No {:?} binary for {}: {:#}", method, self.spec.name, e));
+                    false
+                }
+            }
+        })
+    }
+}
+
+impl Step for CargoCrate {
+    fn id(&self) -> String {
+        format!("rust.crate.{}", self.spec.name)
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        match &self.spec.version {
+            Some(version) => format!("Install {} {}", self.spec.name, version),
+            None => format!("Install {}", self.spec.name),
+        }
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        let mut dependencies = vec![
+            "rust.base-packages".to_string(),
+            "rust.toolchain".to_string(),
+            "rust.cargo-binstall".to_string(),
+        ];
+        dependencies.extend(self.package_steps.iter().cloned());
+        dependencies
+    }
+
+    fn locks(&self) -> Vec<Lock> {
+        vec![Lock::CargoRegistry]
+    }
+
+    fn check(&self) -> Result<bool> {
+        let output = match Command::new(rustup_bin("cargo")).args(["install", "--list"]).output() {
+            Ok(output) if output.status.success() => output,
+            _ => return Ok(false),
+        };
+
+        self.spec.is_satisfied_by(&parse_install_list(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    fn apply(&self, notes: &mut StepNotes) -> Result<()> {
+        let started = Instant::now();
+        let method = match self.install_prebuilt() {
+            Some(method) => method,
+            None => {
+                let args = self.spec.install_args();
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                run_cmd(&rustup_bin("cargo"), &args)
+                    .with_context(|| format!("Failed to install {}", self.spec.name))?;
+                InstallMethod::Source
+            }
+        };
+
+        let detail = method.describe(&self.spec.name, started.elapsed());
+        logging::info(format!("Installed {}: {}", self.spec.name, detail));
+        notes.detail = Some(detail);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crates_from_env() {
+        assert_eq!(crates(None).unwrap(), catalog::get_cargo_packages());
+        let configured = crates(Some("dioxus-cli@0.6, taplo-cli?git=https://github.com/tamasfe/taplo&tag=0.9.3,")).unwrap();
+        assert_eq!(configured.len(), 2);
+        assert_eq!(configured[1].install_args().join(" "), "install --git https://github.com/tamasfe/taplo --tag 0.9.3 --locked taplo-cli");
+        assert!(crates(Some("zellij?nightly")).is_err());
+    }
+
+    #[test]
+    fn test_binstall_args_and_methods() {
+        assert_eq!(prebuilt_methods(None), vec![InstallMethod::Release]);
+        assert_eq!(
+            prebuilt_methods(Some("https://mirror.example")),
+            vec![InstallMethod::Mirror, InstallMethod::Release]
+        );
+
+        let args = binstall_args("zellij", InstallMethod::Mirror, Some("https://mirror.example/cargo/"));
+        assert_eq!(
+            args.join(" "),
+            "--no-confirm --disable-strategies quick-install,compile \
+             --pkg-url https://mirror.example/cargo/{ name }-{ version }-{ target }.tgz --pkg-fmt tgz zellij"
+        );
+        assert_eq!(binstall_args("zellij", InstallMethod::Release, None).last().unwrap(), "zellij");
+    }
+
+    #[test]
+    fn test_describe_reports_time_saved() {
+        assert_eq!(
+            InstallMethod::Release.describe("zellij", Duration::from_secs(20)),
+            "prebuilt release, saved about 9m 40s"
+        );
+        assert_eq!(
+            InstallMethod::Mirror.describe("unknown-crate", Duration::from_secs(300)),
+            "prebuilt from mirror"
+        );
+        assert_eq!(InstallMethod::Source.describe("zellij", Duration::from_secs(600)), "built from source");
+    }
+}
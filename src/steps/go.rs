@@ -0,0 +1,214 @@
+use anyhow::{anyhow, Context, Result};
+use dirs::home_dir;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use super::{Step, StepNotes};
+use crate::package::{catalog, download, sys};
+use crate::system::run_cmd;
+
+const COMPONENT: &str = "Go";
+pub(crate) const DOWNLOAD_URL: &str = "https://dl.google.com/go";
+
+/// Release installed from the official tarball
+pub(crate) const GO_VERSION: &str = "1.23.4";
+
+/// Comma separated `package@version` list replacing the catalog's Go tools
+const TOOLS_VAR: &str = "CYRUP_GO_TOOLS";
+
+/// GOROOT, where the tarball is extracted
+fn go_root() -> Result<PathBuf> {
+    let home = home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+    Ok(home.join(".local/share/go"))
+}
+
+/// GOPATH, which the shell setup defaults to `~/go` like Go itself
+fn go_path() -> Result<PathBuf> {
+    match env::var_os("GOPATH").filter(|path| !path.is_empty()) {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => Ok(home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?.join("go")),
+    }
+}
+
+fn go_bin() -> Result<String> {
+    Ok(go_root()?.join("bin/go").display().to_string())
+}
+
+/// Official archive name, e.g. `go1.23.4.linux-amd64.tar.gz`
+///
+/// `os` and `arch` use `sys::detect_platform` naming, which matches Go's.
+fn archive_name(os: &str, arch: &str) -> String {
+    format!("go{}.{}-{}.tar.gz", GO_VERSION, os, arch)
+}
+
+/// Tools for `go install`, from `CYRUP_GO_TOOLS` if set
+fn configured_tools() -> Vec<String> {
+    tools(env::var(TOOLS_VAR).ok().as_deref())
+}
+
+fn tools(configured: Option<&str>) -> Vec<String> {
+    match configured {
+        Some(list) => list
+            .split(',')
+            .map(str::trim)
+            .filter(|tool| !tool.is_empty())
+            .map(String::from)
+            .collect(),
+        None => catalog::get_go_tools().into_iter().map(String::from).collect(),
+    }
+}
+
+/// Binary `go install` builds for `package`
+///
+/// That is the last path element, skipping a major version suffix like
+/// `/v2`.
+fn binary_name(package: &str) -> &str {
+    let path = package.split('@').next().unwrap_or(package);
+    let mut segments = path.rsplit('/');
+    let last = segments.next().unwrap_or(path);
+    let is_major_version = last
+        .strip_prefix('v')
+        .is_some_and(|major| !major.is_empty() && major.chars().all(|c| c.is_ascii_digit()));
+    match segments.next() {
+        Some(parent) if is_major_version => parent,
+        _ => last,
+    }
+}
+
+/// All steps needed for the Go toolchain
+pub fn steps() -> Vec<Box<dyn Step>> {
+    let mut steps: Vec<Box<dyn Step>> = vec![Box::new(GoToolchain)];
+    for package in configured_tools() {
+        steps.push(Box::new(GoTool { package }));
+    }
+    steps
+}
+
+/// Install Go from the official tarball into `~/.local/share/go`
+///
+/// Distro packages usually lag several releases behind.
+pub struct GoToolchain;
+
+impl Step for GoToolchain {
+    fn id(&self) -> String {
+        "go.toolchain".to_string()
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        format!("Install Go {}", GO_VERSION)
+    }
+
+    fn check(&self) -> Result<bool> {
+        let root = go_root()?;
+        // The first line of GOROOT/VERSION is e.g. `go1.23.4`
+        let version = fs::read_to_string(root.join("VERSION")).unwrap_or_default();
+        Ok(version.lines().next() == Some(format!("go{}", GO_VERSION).as_str()) && root.join("bin/go").exists())
+    }
+
+    fn apply(&self, _notes: &mut StepNotes) -> Result<()> {
+        let (os, arch) = sys::detect_platform()?;
+        let archive = archive_name(&os, &arch);
+        let url = format!("{}/{}", DOWNLOAD_URL, archive);
+        let tarball = env::temp_dir().join(&archive);
+
+        download::fetch(&url, &tarball)?;
+        let checksum = download::fetch_text(&format!("{}.sha256", url))?;
+        download::verify_sha256(&tarball, &checksum)?;
+
+        // Extracting over an older release leaves stale files behind
+        let root = go_root()?;
+        if root.exists() {
+            fs::remove_dir_all(&root).with_context(|| format!("Failed to remove {}", root.display()))?;
+        }
+        // The archive holds a single `go` directory
+        let parent = root.parent().ok_or_else(|| anyhow!("Invalid Go path"))?;
+        fs::create_dir_all(parent)?;
+        let parent = parent.to_str().ok_or_else(|| anyhow!("Invalid Go path"))?;
+        let tarball_path = tarball.to_str().ok_or_else(|| anyhow!("Invalid temp path"))?;
+        run_cmd("tar", &["-C", parent, "-xzf", tarball_path]).context("Failed to unpack Go")?;
+
+        let _ = fs::remove_file(&tarball);
+        Ok(())
+    }
+
+    fn uninstall(&self) -> Result<bool> {
+        let root = go_root()?;
+        if root.exists() {
+            fs::remove_dir_all(&root).with_context(|| format!("Failed to remove {}", root.display()))?;
+        }
+        Ok(true)
+    }
+}
+
+/// Install a tool into `GOPATH/bin` with `go install`
+pub struct GoTool {
+    package: String,
+}
+
+impl GoTool {
+    fn binary(&self) -> Result<PathBuf> {
+        Ok(go_path()?.join("bin").join(binary_name(&self.package)))
+    }
+}
+
+impl Step for GoTool {
+    fn id(&self) -> String {
+        format!("go.tool.{}", binary_name(&self.package))
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        format!("Install {}", binary_name(&self.package))
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["go.toolchain".to_string()]
+    }
+
+    fn check(&self) -> Result<bool> {
+        Ok(self.binary()?.exists())
+    }
+
+    fn apply(&self, _notes: &mut StepNotes) -> Result<()> {
+        run_cmd(&go_bin()?, &["install", &self.package])
+            .with_context(|| format!("Failed to install {}", self.package))
+    }
+
+    fn uninstall(&self) -> Result<bool> {
+        let binary = self.binary()?;
+        if binary.exists() {
+            fs::remove_file(&binary).with_context(|| format!("Failed to remove {}", binary.display()))?;
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_and_tool_names() {
+        assert_eq!(archive_name("darwin", "arm64"), "go1.23.4.darwin-arm64.tar.gz");
+
+        assert_eq!(binary_name("golang.org/x/tools/gopls@latest"), "gopls");
+        assert_eq!(binary_name("github.com/go-delve/delve/cmd/dlv@v1.24.0"), "dlv");
+        assert_eq!(binary_name("github.com/golangci/golangci-lint/v2/cmd/golangci-lint"), "golangci-lint");
+        assert_eq!(binary_name("github.com/mikefarah/yq/v4@latest"), "yq");
+
+        assert_eq!(
+            tools(Some(" mvdan.cc/gofumpt@latest, ,gotest.tools/gotestsum@v1.12.0")),
+            vec!["mvdan.cc/gofumpt@latest", "gotest.tools/gotestsum@v1.12.0"]
+        );
+        assert!(tools(Some("")).is_empty());
+        assert_eq!(tools(None).len(), catalog::get_go_tools().len());
+    }
+}
@@ -0,0 +1,244 @@
+use anyhow::{anyhow, Context, Result};
+use dirs::home_dir;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::python::{uv, PYTHON_VERSIONS};
+use super::{Step, StepNotes};
+use crate::gpu::Accelerator;
+use crate::menu::InstallPlan;
+use crate::system::run_cmd;
+
+const COMPONENT: &str = "Cyrup ML";
+
+/// Jupyter kernel name, also the kernelspec directory name
+pub(crate) const KERNEL_NAME: &str = "cyrup-ml";
+
+/// Libraries in the ML environment
+///
+/// torch comes from the PyTorch index for the plan's accelerator.
+const LIBRARIES: &[&str] = &["numpy", "pandas", "torch", "jupyterlab", "ipykernel"];
+
+/// uv project holding the environment, its lockfile and `.venv`
+pub fn project_dir() -> Result<PathBuf> {
+    let home = home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+    Ok(home.join(".local/share/cyrup/ml"))
+}
+
+fn venv_python() -> Result<PathBuf> {
+    Ok(project_dir()?.join(".venv/bin/python"))
+}
+
+/// Where `ipykernel install --user` puts the kernelspec
+fn kernel_dir() -> Result<PathBuf> {
+    let home = home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+    let kernels = if cfg!(target_os = "macos") {
+        home.join("Library/Jupyter/kernels")
+    } else {
+        home.join(".local/share/jupyter/kernels")
+    };
+    Ok(kernels.join(KERNEL_NAME))
+}
+
+/// Name Jupyter shows for the kernel, e.g. `Cyrup ML (CPU only)`
+pub(crate) fn display_name(accelerator: Accelerator) -> String {
+    format!("Cyrup ML ({})", accelerator)
+}
+
+/// Whether a kernelspec runs `python` and is shown as `display_name`
+///
+/// A kernel registered for another accelerator keeps its old name, so it
+/// has to be registered again.
+fn kernel_matches(kernel_json: &str, python: &Path, display_name: &str) -> bool {
+    let Ok(kernel) = serde_json::from_str::<serde_json::Value>(kernel_json) else {
+        return false;
+    };
+    kernel["display_name"] == display_name && kernel["argv"][0].as_str() == python.to_str()
+}
+
+/// All steps for the ML environment of `plan`
+pub fn steps(plan: &InstallPlan) -> Vec<Box<dyn Step>> {
+    vec![
+        Box::new(MlEnvironment {
+            accelerator: plan.accelerator,
+            pytorch_index: plan.pytorch_index.clone(),
+        }),
+        Box::new(MlKernel { accelerator: plan.accelerator }),
+    ]
+}
+
+/// `pyproject.toml` for the ML project
+///
+/// Without an index, e.g. on Apple silicon, torch comes from PyPI.
+pub(crate) fn pyproject(pytorch_index: Option<&str>) -> String {
+    let dependencies: String = LIBRARIES.iter().map(|library| format!("    \"{}\",\n", library)).collect();
+    let mut pyproject = format!(
+        "# Managed by setcyrup, changes are overwritten on the next install\n\
+         [project]\n\
+         name = \"cyrup-ml\"\n\
+         version = \"0.1.0\"\n\
+         requires-python = \"=={}.*\"\n\
+         dependencies = [\n{}]\n\
+         \n\
+         [tool.uv]\n\
+         package = false\n",
+        PYTHON_VERSIONS[0], dependencies
+    );
+
+    if let Some(url) = pytorch_index {
+        let name = format!("pytorch-{}", url.trim_end_matches('/').rsplit('/').next().unwrap_or("custom"));
+        pyproject.push_str(&format!(
+            "\n[[tool.uv.index]]\nname = \"{name}\"\nurl = \"{url}\"\nexplicit = true\n\
+             \n[tool.uv.sources]\ntorch = [{{ index = \"{name}\" }}]\n"
+        ));
+    }
+    pyproject
+}
+
+/// Create the ML virtualenv from a locked uv project
+pub struct MlEnvironment {
+    accelerator: Accelerator,
+    pytorch_index: Option<String>,
+}
+
+impl Step for MlEnvironment {
+    fn id(&self) -> String {
+        "ml.environment".to_string()
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        format!("Create the ML environment ({})", self.accelerator)
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["python.toolchain".to_string()]
+    }
+
+    /// Satisfied while the project matches the plan and is locked
+    fn check(&self) -> Result<bool> {
+        let dir = project_dir()?;
+        let current = fs::read_to_string(dir.join("pyproject.toml")).unwrap_or_default();
+        Ok(current == pyproject(self.pytorch_index.as_deref())
+            && dir.join("uv.lock").exists()
+            && venv_python()?.exists())
+    }
+
+    fn apply(&self, notes: &mut StepNotes) -> Result<()> {
+        let dir = project_dir()?;
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let pyproject_path = dir.join("pyproject.toml");
+        fs::write(&pyproject_path, pyproject(self.pytorch_index.as_deref()))
+            .with_context(|| format!("Failed to write {}", pyproject_path.display()))?;
+
+        let project = dir.to_str().ok_or_else(|| anyhow!("Invalid project path"))?;
+        let uv = uv()?;
+        // Writes uv.lock, then installs exactly what it pins
+        run_cmd(&uv, &["lock", "--project", project]).context("Failed to lock the ML environment")?;
+        run_cmd(&uv, &["sync", "--locked", "--project", project, "--python", PYTHON_VERSIONS[0]])
+            .context("Failed to install the ML environment")?;
+
+        notes
+            .follow_ups
+            .push(format!("Activate the ML environment with `source {}/.venv/bin/activate`", project));
+        Ok(())
+    }
+
+    fn uninstall(&self) -> Result<bool> {
+        let dir = project_dir()?;
+        if dir.exists() {
+            fs::remove_dir_all(&dir).with_context(|| format!("Failed to remove {}", dir.display()))?;
+        }
+        Ok(true)
+    }
+}
+
+/// Register the environment as a Jupyter kernel
+pub struct MlKernel {
+    accelerator: Accelerator,
+}
+
+impl Step for MlKernel {
+    fn id(&self) -> String {
+        "ml.kernel".to_string()
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        "Register the Jupyter kernel".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["ml.environment".to_string()]
+    }
+
+    fn check(&self) -> Result<bool> {
+        let kernel_json = fs::read_to_string(kernel_dir()?.join("kernel.json")).unwrap_or_default();
+        Ok(kernel_matches(&kernel_json, &venv_python()?, &display_name(self.accelerator)))
+    }
+
+    fn apply(&self, _notes: &mut StepNotes) -> Result<()> {
+        let python = venv_python()?;
+        let python = python.to_str().ok_or_else(|| anyhow!("Invalid venv path"))?;
+        let display_name = display_name(self.accelerator);
+        run_cmd(
+            python,
+            &["-m", "ipykernel", "install", "--user", "--name", KERNEL_NAME, "--display-name", &display_name],
+        )
+        .context("Failed to register the Jupyter kernel")
+    }
+
+    fn uninstall(&self) -> Result<bool> {
+        let dir = kernel_dir()?;
+        if dir.exists() {
+            fs::remove_dir_all(&dir).with_context(|| format!("Failed to remove {}", dir.display()))?;
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pyproject_pins_torch_to_the_accelerator_index() {
+        let cuda = pyproject(Some("https://download.pytorch.org/whl/cu124"));
+        assert!(cuda.contains("requires-python = \"==3.12.*\"\n"));
+        assert!(cuda.contains("    \"torch\",\n"));
+        assert!(cuda.ends_with(
+            "[[tool.uv.index]]\n\
+             name = \"pytorch-cu124\"\n\
+             url = \"https://download.pytorch.org/whl/cu124\"\n\
+             explicit = true\n\
+             \n\
+             [tool.uv.sources]\n\
+             torch = [{ index = \"pytorch-cu124\" }]\n"
+        ));
+
+        let metal = pyproject(None);
+        assert!(!metal.contains("tool.uv.index"));
+        assert!(metal.ends_with("[tool.uv]\npackage = false\n"));
+    }
+
+    #[test]
+    fn test_kernel_matches_plan() {
+        let python = Path::new("/home/dev/.local/share/cyrup/ml/.venv/bin/python");
+        let kernel = r#"{
+ "argv": ["/home/dev/.local/share/cyrup/ml/.venv/bin/python", "-m", "ipykernel_launcher", "-f", "{connection_file}"],
+ "display_name": "Cyrup ML (CPU only)",
+ "language": "python"
+}"#;
+        assert!(kernel_matches(kernel, python, &display_name(Accelerator::Cpu)));
+        // Registered for another accelerator or interpreter
+        assert!(!kernel_matches(kernel, python, &display_name(Accelerator::Cuda)));
+        assert!(!kernel_matches(kernel, Path::new("/usr/bin/python3"), &display_name(Accelerator::Cpu)));
+        assert!(!kernel_matches("", python, &display_name(Accelerator::Cpu)));
+    }
+}
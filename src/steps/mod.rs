@@ -0,0 +1,213 @@
+pub mod cargo;
+pub mod go;
+pub mod ml;
+pub mod node;
+pub mod packages;
+pub mod path;
+pub mod python;
+pub mod release;
+pub mod rust;
+pub mod setup;
+
+use anyhow::Result;
+use std::collections::HashSet;
+
+use crate::menu::{InstallPlan, Toolchain};
+
+/// Notes a step leaves for the summary screen
+#[derive(Debug, Default)]
+pub struct StepNotes {
+    /// Non-fatal problems found while running the step
+    pub warnings: Vec<String>,
+    /// Actions the user must take once installation finishes
+    pub follow_ups: Vec<String>,
+    /// How the step went, shown next to it, e.g. "prebuilt release"
+    pub detail: Option<String>,
+}
+
+/// A resource only one step may use at a time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lock {
+    /// The system package database (dpkg, rpm or Homebrew)
+    PackageManager,
+    /// The cargo registry and install root
+    ///
+    /// Each `cargo install` already builds in parallel, so running several
+    /// at once mostly contends for the registry lock and CPU.
+    CargoRegistry,
+    /// The user's shell rc files
+    ShellRc,
+}
+
+/// A single idempotent unit of installation work
+///
+/// The engine calls `check` first and skips the step when it is already
+/// satisfied. Otherwise it calls `apply` and then `verify`, failing the
+/// step if the postcondition still does not hold.
+///
+/// # Implementation Requirements
+/// 1. `id` must be stable across runs, it keys the checkpoint store
+/// 2. `check` and `verify` must be cheap and not require network access
+/// 3. `apply` must be safe to run again after a partial failure
+/// 4. Shared resources must be declared through `locks`
+pub trait Step: Send + Sync {
+    /// Stable identifier, e.g. `rust.crate.zellij`
+    fn id(&self) -> String;
+
+    /// Component shown on the summary screen
+    fn component(&self) -> &str;
+
+    /// Human readable step name
+    fn name(&self) -> String;
+
+    /// Whether the step's goal already holds, so `apply` can be skipped
+    fn check(&self) -> Result<bool>;
+
+    /// Perform the step
+    fn apply(&self, notes: &mut StepNotes) -> Result<()>;
+
+    /// Whether `apply` achieved the step's goal
+    fn verify(&self) -> Result<bool> {
+        self.check()
+    }
+
+    /// Ids of steps that must succeed before this one starts
+    ///
+    /// Dependencies that are not part of the plan are ignored.
+    fn dependencies(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Resources held exclusively while this step runs
+    fn locks(&self) -> Vec<Lock> {
+        Vec::new()
+    }
+
+    /// Undo `apply` for `setcyrup uninstall`
+    ///
+    /// Returns `false` for steps that leave their work in place, like
+    /// system packages other software may rely on.
+    fn uninstall(&self) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+/// Build the ordered list of steps for an installation plan
+///
+/// Components that share work, like Rust for both Secret.Rust and
+/// Cyrup Developer, only get one copy of each step.
+pub fn for_plan(plan: &InstallPlan) -> Vec<Box<dyn Step>> {
+    let mut steps: Vec<Box<dyn Step>> = vec![Box::new(setup::ConfigDir)];
+
+    for component in &plan.components {
+        if plan.installs_system_packages() && !component.dependencies_for(plan.accelerator).is_empty() {
+            steps.push(Box::new(packages::SystemPackages::for_component(component, plan)));
+        }
+
+        for toolchain in &component.toolchains {
+            match toolchain {
+                Toolchain::Rust => steps.extend(rust::steps(plan)),
+                Toolchain::Python => steps.extend(python::steps(plan.privilege)),
+                Toolchain::Node => steps.extend(node::steps()),
+                Toolchain::Go => steps.extend(go::steps()),
+                Toolchain::Releases => steps.extend(release::steps()),
+                // The environment is built with uv
+                Toolchain::Ml => {
+                    steps.extend(python::steps(plan.privilege));
+                    steps.extend(ml::steps(plan));
+                }
+            }
+        }
+    }
+
+    steps.push(Box::new(setup::Shell));
+    steps.push(Box::new(setup::Gpu));
+
+    let mut seen = HashSet::new();
+    steps.retain(|step| seen.insert(step.id()));
+    steps
+}
+
+/// Turn a display name into an id fragment, e.g. "Cyrup ML" -> "cyrup-ml"
+pub fn slug(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::Accelerator;
+    use crate::menu::{InstallModule, InstallTarget};
+    use crate::package::Privilege;
+    use rust::RustSpec;
+
+    fn module(name: &str, dependencies: &[&str], toolchains: Vec<Toolchain>) -> InstallModule {
+        InstallModule {
+            name: name.to_string(),
+            description: String::new(),
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            variants: vec![],
+            toolchains,
+            services: vec![],
+            disk_mb: 0,
+            commands: vec![],
+        }
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("Secret.Rust (cysec)"), "secret-rust-cysec");
+        assert_eq!(slug("Cyrup ML"), "cyrup-ml");
+    }
+
+    #[test]
+    fn test_for_plan_ids_are_unique_and_stable() {
+        let plan = InstallPlan {
+            target: Some(InstallTarget::Host),
+            components: vec![
+                module("Secret.Rust (cysec)", &["libssl-dev"], vec![Toolchain::Rust]),
+                module("Cyrup Developer", &["git"], vec![Toolchain::Rust, Toolchain::Python]),
+            ],
+            accelerator: Accelerator::Cpu,
+            pytorch_index: None,
+            privilege: Privilege::Sudo,
+            rust: RustSpec::default(),
+        };
+
+        let ids: Vec<String> = for_plan(&plan).iter().map(|step| step.id()).collect();
+        let unique: HashSet<&String> = ids.iter().collect();
+        assert_eq!(ids.len(), unique.len());
+
+        assert_eq!(ids.first().map(String::as_str), Some("setup.config-dir"));
+        assert!(ids.contains(&"secret-rust-cysec.packages".to_string()));
+        assert!(ids.contains(&"rust.crate.zellij".to_string()));
+        assert!(ids.contains(&"python.uv".to_string()));
+        assert_eq!(ids.last().map(String::as_str), Some("setup.gpu"));
+
+        // Source builds wait for every component's headers
+        let steps = for_plan(&plan);
+        let zellij = steps.iter().find(|step| step.id() == "rust.crate.zellij").unwrap();
+        assert!(zellij.dependencies().contains(&"secret-rust-cysec.packages".to_string()));
+        assert!(zellij.dependencies().contains(&"cyrup-developer.packages".to_string()));
+    }
+
+    #[test]
+    fn test_user_only_plan_skips_system_packages() {
+        let plan = InstallPlan {
+            target: Some(InstallTarget::Host),
+            components: vec![module("Secret.Rust (cysec)", &["libssl-dev"], vec![Toolchain::Rust])],
+            accelerator: Accelerator::Cpu,
+            pytorch_index: None,
+            privilege: Privilege::UserOnly,
+            rust: RustSpec::default(),
+        };
+
+        let ids: Vec<String> = for_plan(&plan).iter().map(|step| step.id()).collect();
+        assert!(!ids.iter().any(|id| id.ends_with("packages")));
+        assert!(ids.contains(&"rust.rustup".to_string()));
+    }
+}
@@ -0,0 +1,202 @@
+use anyhow::{anyhow, Context, Result};
+use dirs::home_dir;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use super::{Step, StepNotes};
+use crate::system::run_cmd;
+
+const COMPONENT: &str = "Node.js";
+pub(crate) const FNM_INSTALL_URL: &str = "https://fnm.vercel.app/install";
+
+/// LTS release installed and made the fnm default
+pub(crate) const NODE_VERSION: &str = "22.11.0";
+
+/// fnm's install directory, also where it keeps Node versions
+pub(crate) fn fnm_dir() -> Result<PathBuf> {
+    let home = home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+    Ok(home.join(".local/share/fnm"))
+}
+
+/// Path to the fnm binary, which is not on PATH until the shell restarts
+fn fnm() -> Result<String> {
+    Ok(fnm_dir()?.join("fnm").display().to_string())
+}
+
+/// bin directory of the pinned Node version
+fn node_bin_dir() -> Result<PathBuf> {
+    Ok(fnm_dir()?.join(format!("node-versions/v{}/installation/bin", NODE_VERSION)))
+}
+
+/// Versions from `fnm list` with their aliases
+///
+/// Lines look like `* v22.11.0 default, lts-latest` or `* system`.
+pub(crate) fn parse_fnm_list(output: &str) -> Vec<(String, Vec<String>)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start_matches('*').trim();
+            let (version, aliases) = line.split_once(' ').unwrap_or((line, ""));
+            let aliases = aliases
+                .split(',')
+                .map(str::trim)
+                .filter(|alias| !alias.is_empty())
+                .map(String::from)
+                .collect();
+            Some((version.strip_prefix('v')?.to_string(), aliases))
+        })
+        .collect()
+}
+
+/// Installed versions according to fnm, empty if fnm is missing
+pub(crate) fn installed_versions() -> Vec<(String, Vec<String>)> {
+    let output = fnm().ok().and_then(|fnm| Command::new(fnm).arg("list").output().ok());
+    match output {
+        Some(output) if output.status.success() => parse_fnm_list(&String::from_utf8_lossy(&output.stdout)),
+        _ => Vec::new(),
+    }
+}
+
+/// All steps needed for the Node.js toolchain
+pub fn steps() -> Vec<Box<dyn Step>> {
+    vec![Box::new(Fnm), Box::new(NodeVersion), Box::new(Corepack)]
+}
+
+/// Install fnm into the home directory
+///
+/// Shell integration lives in setcyrup's shell configuration, so the
+/// installer is told to leave rc files alone.
+pub struct Fnm;
+
+impl Step for Fnm {
+    fn id(&self) -> String {
+        "node.fnm".to_string()
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        "Install fnm".to_string()
+    }
+
+    fn check(&self) -> Result<bool> {
+        Ok(fnm_dir()?.join("fnm").exists())
+    }
+
+    fn apply(&self, _notes: &mut StepNotes) -> Result<()> {
+        let installer = env::temp_dir().join("fnm-install.sh");
+        let installer = installer.to_str().ok_or_else(|| anyhow!("Invalid temp path"))?;
+        let dir = fnm_dir()?;
+        let dir = dir.to_str().ok_or_else(|| anyhow!("Invalid fnm path"))?;
+
+        run_cmd("curl", &["--proto", "=https", "--tlsv1.2", "-fsSL", FNM_INSTALL_URL, "-o", installer])
+            .context("Failed to download the fnm installer")?;
+        run_cmd("bash", &[installer, "--install-dir", dir, "--skip-shell"])
+            .context("Failed to run the fnm installer")?;
+        Ok(())
+    }
+
+    /// Removes fnm together with every Node version it installed
+    fn uninstall(&self) -> Result<bool> {
+        let dir = fnm_dir()?;
+        if dir.exists() {
+            fs::remove_dir_all(&dir).with_context(|| format!("Failed to remove {}", dir.display()))?;
+        }
+        Ok(true)
+    }
+}
+
+/// Install the pinned LTS release and make it the default
+pub struct NodeVersion;
+
+impl Step for NodeVersion {
+    fn id(&self) -> String {
+        "node.version".to_string()
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        format!("Install Node.js {}", NODE_VERSION)
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["node.fnm".to_string()]
+    }
+
+    fn check(&self) -> Result<bool> {
+        Ok(installed_versions()
+            .iter()
+            .any(|(version, aliases)| version == NODE_VERSION && aliases.iter().any(|alias| alias == "default")))
+    }
+
+    fn apply(&self, _notes: &mut StepNotes) -> Result<()> {
+        let fnm = fnm()?;
+        run_cmd(&fnm, &["install", NODE_VERSION]).context("Failed to install Node.js")?;
+        run_cmd(&fnm, &["default", NODE_VERSION]).context("Failed to set the default Node.js")?;
+        Ok(())
+    }
+
+    fn uninstall(&self) -> Result<bool> {
+        run_cmd(&fnm()?, &["uninstall", NODE_VERSION]).context("Failed to uninstall Node.js")?;
+        Ok(true)
+    }
+}
+
+/// Enable corepack so `pnpm` and `yarn` use the versions projects pin
+pub struct Corepack;
+
+impl Step for Corepack {
+    fn id(&self) -> String {
+        "node.corepack".to_string()
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        "Enable corepack".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["node.version".to_string()]
+    }
+
+    fn check(&self) -> Result<bool> {
+        let bin = node_bin_dir()?;
+        Ok(bin.join("pnpm").exists() && bin.join("yarn").exists())
+    }
+
+    fn apply(&self, _notes: &mut StepNotes) -> Result<()> {
+        let using = format!("--using={}", NODE_VERSION);
+        run_cmd(&fnm()?, &["exec", &using, "corepack", "enable"]).context("Failed to enable corepack")
+    }
+
+    fn uninstall(&self) -> Result<bool> {
+        let using = format!("--using={}", NODE_VERSION);
+        run_cmd(&fnm()?, &["exec", &using, "corepack", "disable"]).context("Failed to disable corepack")?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fnm_list() {
+        let output = "* v20.18.1\n* v22.11.0 default, lts-latest\n* system\n";
+        let versions = parse_fnm_list(output);
+        assert_eq!(versions.len(), 2);
+        assert!(versions[0].1.is_empty());
+        assert_eq!(versions[1].0, "22.11.0");
+        assert_eq!(versions[1].1, vec!["default", "lts-latest"]);
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::Result;
+
+use super::{slug, Lock, Step, StepNotes};
+use crate::menu::{InstallModule, InstallPlan};
+use crate::package::{manager::PackageManager, Package, Privilege};
+
+/// Install a group of system packages with the detected package manager
+pub struct SystemPackages {
+    id: String,
+    component: String,
+    packages: Vec<Package>,
+    privilege: Privilege,
+}
+
+impl SystemPackages {
+    pub fn new(
+        id: impl Into<String>,
+        component: impl Into<String>,
+        packages: Vec<Package>,
+        privilege: Privilege,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            component: component.into(),
+            packages,
+            privilege,
+        }
+    }
+
+    /// Id of the step installing a menu component's system packages
+    pub fn id_for(component: &InstallModule) -> String {
+        format!("{}.packages", slug(&component.name))
+    }
+
+    /// System packages a menu component depends on in `plan`
+    pub fn for_component(component: &InstallModule, plan: &InstallPlan) -> Self {
+        Self::new(
+            Self::id_for(component),
+            component.name.clone(),
+            component
+                .dependencies_for(plan.accelerator)
+                .iter()
+                .map(Package::new)
+                .collect(),
+            plan.privilege,
+        )
+    }
+}
+
+impl Step for SystemPackages {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn component(&self) -> &str {
+        &self.component
+    }
+
+    fn name(&self) -> String {
+        "Install system packages".to_string()
+    }
+
+    fn locks(&self) -> Vec<Lock> {
+        vec![Lock::PackageManager]
+    }
+
+    fn check(&self) -> Result<bool> {
+        let pm = PackageManager::detect()?;
+        for package in &self.packages {
+            if !pm.is_installed(package)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn apply(&self, _notes: &mut StepNotes) -> Result<()> {
+        PackageManager::detect()?.install(&self.packages, self.privilege)
+    }
+}
@@ -0,0 +1,86 @@
+use anyhow::{anyhow, Context, Result};
+use dirs::home_dir;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::{Lock, Step, StepNotes};
+
+/// Shell rc files that get PATH entries, if they exist
+const RC_FILES: &[&str] = &[".bashrc", ".zshrc"];
+
+/// Add a directory to PATH in the user's shell rc files
+pub struct PathEntry {
+    id: String,
+    component: String,
+    dir: PathBuf,
+}
+
+impl PathEntry {
+    pub fn new(id: impl Into<String>, component: impl Into<String>, dir: PathBuf) -> Self {
+        Self {
+            id: id.into(),
+            component: component.into(),
+            dir,
+        }
+    }
+
+    fn export_line(&self) -> String {
+        format!("export PATH=\"{}:$PATH\"", self.dir.display())
+    }
+
+    fn rc_files() -> Result<Vec<PathBuf>> {
+        let home = home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+        Ok(RC_FILES
+            .iter()
+            .map(|rc| home.join(rc))
+            .filter(|path| path.exists())
+            .collect())
+    }
+}
+
+impl Step for PathEntry {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn component(&self) -> &str {
+        &self.component
+    }
+
+    fn name(&self) -> String {
+        format!("Add {} to PATH", self.dir.display())
+    }
+
+    fn locks(&self) -> Vec<Lock> {
+        vec![Lock::ShellRc]
+    }
+
+    fn check(&self) -> Result<bool> {
+        let line = self.export_line();
+        for rc_path in Self::rc_files()? {
+            if !fs::read_to_string(&rc_path)?.contains(&line) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn apply(&self, _notes: &mut StepNotes) -> Result<()> {
+        let line = self.export_line();
+        for rc_path in Self::rc_files()? {
+            // Only touch files that don't have the entry yet
+            if fs::read_to_string(&rc_path)?.contains(&line) {
+                continue;
+            }
+
+            let mut file = OpenOptions::new()
+                .append(true)
+                .open(&rc_path)
+                .with_context(|| format!("Failed to open {}", rc_path.display()))?;
+            writeln!(file, "{}", line)
+                .with_context(|| format!("Failed to write {}", rc_path.display()))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,319 @@
+use anyhow::{anyhow, Context, Result};
+use dirs::home_dir;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use super::{packages::SystemPackages, path::PathEntry, Step, StepNotes};
+use crate::package::{catalog, Privilege};
+use crate::system::run_cmd;
+
+const COMPONENT: &str = "Python";
+pub(crate) const UV_INSTALL_URL: &str = "https://astral.sh/uv/install.sh";
+
+/// Interpreters installed with `uv python install`; the first is the global default
+pub(crate) const PYTHON_VERSIONS: &[&str] = &["3.12", "3.11"];
+
+/// Header of the config files setcyrup writes
+const MANAGED_MARKER: &str = "# Managed by setcyrup";
+
+/// Python CLIs installed with `uv tool install`
+///
+/// `with` adds packages to the tool's environment, e.g. JupyterLab for
+/// the `jupyter` command from jupyter-core.
+pub(crate) const TOOLS: &[(&str, &[&str])] = &[("ruff", &[]), ("jupyter-core", &["jupyterlab"])];
+
+/// Directory holding uv, uvx and the executables of uv tools
+pub(crate) fn uv_bin_dir() -> Result<PathBuf> {
+    let home = home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+    Ok(home.join(".local/bin"))
+}
+
+/// Path to the uv binary, which is not on PATH until the shell restarts
+pub(crate) fn uv() -> Result<String> {
+    Ok(uv_bin_dir()?.join("uv").display().to_string())
+}
+
+/// uv's user configuration directory
+fn uv_config_dir() -> Result<PathBuf> {
+    let home = home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+    Ok(env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".config"))
+        .join("uv"))
+}
+
+/// Run uv and return its stdout, or `None` if it failed
+fn uv_output(args: &[&str]) -> Option<String> {
+    let output = Command::new(uv().ok()?).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// All steps needed for the Python toolchain
+pub fn steps(privilege: Privilege) -> Vec<Box<dyn Step>> {
+    let mut steps: Vec<Box<dyn Step>> = Vec::new();
+    if privilege.can_elevate() {
+        steps.push(Box::new(SystemPackages::new(
+            "python.base-packages",
+            COMPONENT,
+            catalog::get_many(&["python-base"]),
+            privilege,
+        )));
+    }
+    steps.push(Box::new(Uv));
+
+    if let Ok(dir) = uv_bin_dir() {
+        steps.push(Box::new(PathEntry::new("python.path", COMPONENT, dir)));
+    }
+
+    steps.push(Box::new(PythonInterpreters));
+    steps.push(Box::new(UvConfig));
+    for (package, with) in TOOLS {
+        steps.push(Box::new(UvTool { package, with }));
+    }
+    steps
+}
+
+/// Install uv with Astral's standalone installer
+pub struct Uv;
+
+impl Step for Uv {
+    fn id(&self) -> String {
+        "python.uv".to_string()
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        "Install uv".to_string()
+    }
+
+    fn check(&self) -> Result<bool> {
+        Ok(uv_bin_dir()?.join("uv").exists())
+    }
+
+    fn apply(&self, _notes: &mut StepNotes) -> Result<()> {
+        let installer = env::temp_dir().join("uv-installer.sh");
+        let installer = installer.to_str().ok_or_else(|| anyhow!("Invalid temp path"))?;
+
+        run_cmd("curl", &["--proto", "=https", "--tlsv1.2", "-LsSf", UV_INSTALL_URL, "-o", installer])
+            .context("Failed to download the uv installer")?;
+        // PATH is handled by the python.path step
+        run_cmd("sh", &[installer, "--no-modify-path"]).context("Failed to run the uv installer")?;
+        Ok(())
+    }
+
+    fn uninstall(&self) -> Result<bool> {
+        let uv = uv()?;
+        run_cmd(&uv, &["cache", "clean"]).context("Failed to clean the uv cache")?;
+        let bin_dir = uv_bin_dir()?;
+        for binary in ["uv", "uvx"] {
+            let path = bin_dir.join(binary);
+            if path.exists() {
+                fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Install the pinned interpreters with `uv python install`
+pub struct PythonInterpreters;
+
+impl Step for PythonInterpreters {
+    fn id(&self) -> String {
+        "python.toolchain".to_string()
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        format!("Install Python {}", PYTHON_VERSIONS.join(", "))
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["python.base-packages".to_string(), "python.uv".to_string()]
+    }
+
+    fn check(&self) -> Result<bool> {
+        let installed = match uv_output(&["python", "list", "--only-installed", "--python-preference", "only-managed"]) {
+            Some(output) => parse_python_list(&output),
+            None => return Ok(false),
+        };
+        Ok(PYTHON_VERSIONS.iter().all(|wanted| {
+            installed
+                .iter()
+                .any(|version| version == wanted || version.starts_with(&format!("{}.", wanted)))
+        }))
+    }
+
+    fn apply(&self, _notes: &mut StepNotes) -> Result<()> {
+        let mut args = vec!["python", "install"];
+        args.extend(PYTHON_VERSIONS);
+        run_cmd(&uv()?, &args).context("Failed to install Python")
+    }
+
+    fn uninstall(&self) -> Result<bool> {
+        let mut args = vec!["python", "uninstall"];
+        args.extend(PYTHON_VERSIONS);
+        run_cmd(&uv()?, &args).context("Failed to uninstall Python")?;
+        Ok(true)
+    }
+}
+
+/// Versions from `uv python list`, whose lines start with keys like
+/// `cpython-3.12.7-linux-x86_64-gnu`
+fn parse_python_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .filter_map(|key| key.strip_prefix("cpython-"))
+        .filter_map(|rest| rest.split('-').next())
+        .map(String::from)
+        .collect()
+}
+
+/// Global uv settings and the default interpreter pin
+///
+/// Files the user wrote themselves are left untouched.
+pub struct UvConfig;
+
+impl UvConfig {
+    fn files() -> Result<Vec<(PathBuf, String)>> {
+        let dir = uv_config_dir()?;
+        Ok(vec![
+            (
+                dir.join("uv.toml"),
+                format!(
+                    "{}\n# Prefer interpreters installed by uv over the system Python\npython-preference = \"managed\"\n",
+                    MANAGED_MARKER
+                ),
+            ),
+            (dir.join(".python-version"), format!("{}\n", PYTHON_VERSIONS[0])),
+        ])
+    }
+}
+
+impl Step for UvConfig {
+    fn id(&self) -> String {
+        "python.config".to_string()
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        "Configure uv".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["python.toolchain".to_string()]
+    }
+
+    fn check(&self) -> Result<bool> {
+        Ok(Self::files()?.iter().all(|(path, _)| path.exists()))
+    }
+
+    fn apply(&self, notes: &mut StepNotes) -> Result<()> {
+        for (path, contents) in Self::files()? {
+            if path.exists() {
+                notes.warnings.push(format!("Kept your existing {}", path.display()));
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    fn uninstall(&self) -> Result<bool> {
+        for (path, contents) in Self::files()? {
+            // Only remove what is still exactly as setcyrup wrote it
+            if fs::read_to_string(&path).is_ok_and(|current| current == contents) {
+                fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Install a Python CLI into its own environment with `uv tool install`
+pub struct UvTool {
+    package: &'static str,
+    with: &'static [&'static str],
+}
+
+impl Step for UvTool {
+    fn id(&self) -> String {
+        format!("python.tool.{}", self.package)
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        format!("Install {}", self.package)
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["python.config".to_string()]
+    }
+
+    fn check(&self) -> Result<bool> {
+        Ok(uv_output(&["tool", "list"]).is_some_and(|output| parse_tool_list(&output).contains(&self.package)))
+    }
+
+    fn apply(&self, _notes: &mut StepNotes) -> Result<()> {
+        let mut args = vec!["tool", "install"];
+        for package in self.with {
+            args.extend(["--with", package]);
+        }
+        args.push(self.package);
+        run_cmd(&uv()?, &args).with_context(|| format!("Failed to install {}", self.package))
+    }
+
+    fn uninstall(&self) -> Result<bool> {
+        run_cmd(&uv()?, &["tool", "uninstall", self.package])
+            .with_context(|| format!("Failed to uninstall {}", self.package))?;
+        Ok(true)
+    }
+}
+
+/// Tool names from `uv tool list`
+///
+/// Tools look like `ruff v0.8.4`, followed by `- ruff` lines for their
+/// executables.
+fn parse_tool_list(output: &str) -> Vec<&str> {
+    output
+        .lines()
+        .filter(|line| !line.starts_with('-') && !line.starts_with(char::is_whitespace))
+        .filter_map(|line| line.split_whitespace().next())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_uv_output() {
+        let pythons = "cpython-3.12.7-linux-x86_64-gnu    /home/dev/.local/share/uv/python/cpython-3.12.7-linux-x86_64-gnu/bin/python3.12\n\
+                       cpython-3.11.10-linux-x86_64-gnu   /home/dev/.local/share/uv/python/cpython-3.11.10-linux-x86_64-gnu/bin/python3.11\n";
+        assert_eq!(parse_python_list(pythons), vec!["3.12.7", "3.11.10"]);
+
+        let tools = "jupyter-core v5.7.2\n- jupyter\n- jupyter-migrate\nruff v0.8.4\n- ruff\n";
+        assert_eq!(parse_tool_list(tools), vec!["jupyter-core", "ruff"]);
+    }
+}
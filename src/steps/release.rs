@@ -0,0 +1,61 @@
+use anyhow::Result;
+
+use super::{path::PathEntry, Step, StepNotes};
+use crate::package::release::{ReleaseInstaller, ReleaseSpec};
+use crate::package::{catalog, sys};
+
+const COMPONENT: &str = "Release binaries";
+
+/// All steps for the catalog's release binaries
+pub fn steps() -> Vec<Box<dyn Step>> {
+    let mut steps: Vec<Box<dyn Step>> = Vec::new();
+    if let Ok(installer) = ReleaseInstaller::new() {
+        steps.push(Box::new(PathEntry::new("release.path", COMPONENT, installer.bin_dir().to_path_buf())));
+    }
+    for spec in catalog::get_release_binaries() {
+        steps.push(Box::new(ReleaseBinary { spec }));
+    }
+    steps
+}
+
+/// Install a tool from its release asset into `~/.local/bin`
+///
+/// A recorded version other than the spec's is upgraded in place.
+pub struct ReleaseBinary {
+    pub spec: ReleaseSpec,
+}
+
+impl Step for ReleaseBinary {
+    fn id(&self) -> String {
+        format!("release.{}", self.spec.name)
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        format!("Install {} {}", self.spec.name, self.spec.version)
+    }
+
+    fn check(&self) -> Result<bool> {
+        Ok(ReleaseInstaller::new()?.is_current(&self.spec))
+    }
+
+    fn apply(&self, notes: &mut StepNotes) -> Result<()> {
+        let installer = ReleaseInstaller::new()?;
+        let previous = installer.installed_version(&self.spec.name);
+        let (os, arch) = sys::detect_platform()?;
+        installer.install(&self.spec, &os, &arch)?;
+
+        if let Some(previous) = previous.filter(|previous| *previous != self.spec.version) {
+            notes.detail = Some(format!("upgraded from {}", previous));
+        }
+        Ok(())
+    }
+
+    fn uninstall(&self) -> Result<bool> {
+        ReleaseInstaller::new()?.uninstall(&self.spec)?;
+        Ok(true)
+    }
+}
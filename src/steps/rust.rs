@@ -0,0 +1,477 @@
+use anyhow::{anyhow, Context, Result};
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+use super::cargo::{configured_crates, CargoBinstall, CargoCrate};
+use super::{packages::SystemPackages, path::PathEntry, Step, StepNotes};
+use crate::logging;
+use crate::menu::InstallPlan;
+use crate::package::catalog;
+use crate::system::run_cmd;
+
+pub(super) const COMPONENT: &str = "Rust";
+pub(crate) const RUSTUP_INSTALL_URL: &str = "https://sh.rustup.rs";
+
+/// Components rustup only ships for nightly toolchains
+const NIGHTLY_COMPONENTS: &[&str] = &["miri"];
+
+/// Architectures that start the host triple rustup appends to names,
+/// e.g. `clippy-x86_64-unknown-linux-gnu`
+const TRIPLE_ARCHES: &[&str] = &["x86_64", "aarch64", "i686", "armv7", "arm", "riscv64", "powerpc64", "s390x"];
+
+/// A rustup release channel
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Channel {
+    Stable,
+    Beta,
+    Nightly,
+    /// A dated channel or an exact release, e.g. `nightly-2024-11-28` or `1.82.0`
+    Pinned(String),
+}
+
+impl Channel {
+    fn is_nightly(&self) -> bool {
+        match self {
+            Channel::Nightly => true,
+            Channel::Pinned(name) => name.starts_with("nightly-"),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Channel::Stable => write!(f, "stable"),
+            Channel::Beta => write!(f, "beta"),
+            Channel::Nightly => write!(f, "nightly"),
+            Channel::Pinned(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl FromStr for Channel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let is_date = |date: &str| {
+            let parts: Vec<&str> = date.split('-').collect();
+            parts.len() == 3
+                && [4, 2, 2].iter().zip(&parts).all(|(len, part)| {
+                    part.len() == *len && part.chars().all(|c| c.is_ascii_digit())
+                })
+        };
+        let is_release = |version: &str| {
+            let parts: Vec<&str> = version.split('.').collect();
+            (2..=3).contains(&parts.len())
+                && parts.iter().all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        };
+
+        match s {
+            "stable" => Ok(Channel::Stable),
+            "beta" => Ok(Channel::Beta),
+            "nightly" => Ok(Channel::Nightly),
+            _ if is_release(s) => Ok(Channel::Pinned(s.to_string())),
+            _ => match s.split_once('-') {
+                Some(("stable" | "beta" | "nightly", date)) if is_date(date) => Ok(Channel::Pinned(s.to_string())),
+                _ => Err(anyhow!(
+                    "Invalid Rust toolchain {:?}, expected stable, beta, nightly, nightly-YYYY-MM-DD or a version like 1.82.0",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for Channel {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Channel> for String {
+    fn from(channel: Channel) -> Self {
+        channel.to_string()
+    }
+}
+
+/// The Rust toolchains, components and targets a plan sets up
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RustSpec {
+    /// Toolchain made the rustup default
+    pub channel: Channel,
+    /// Toolchains installed alongside, with the minimal profile
+    pub extra_toolchains: Vec<Channel>,
+    /// Components added to the default toolchain, e.g. `clippy`
+    pub components: Vec<String>,
+    /// Cross compilation targets added to the default toolchain
+    pub targets: Vec<String>,
+}
+
+impl Default for RustSpec {
+    fn default() -> Self {
+        Self {
+            channel: Channel::Stable,
+            extra_toolchains: Vec::new(),
+            components: ["rust-analyzer", "clippy", "rustfmt", "rust-src"].map(String::from).to_vec(),
+            // wasm-pack from the catalog builds for the browser
+            targets: vec!["wasm32-unknown-unknown".to_string()],
+        }
+    }
+}
+
+impl RustSpec {
+    /// Reject combinations rustup cannot install
+    pub fn validate(&self) -> Result<()> {
+        if let Some(component) = self
+            .components
+            .iter()
+            .find(|c| NIGHTLY_COMPONENTS.contains(&c.as_str()))
+        {
+            if !self.channel.is_nightly() {
+                return Err(anyhow!(
+                    "The {} component needs a nightly toolchain, but the default is {}",
+                    component,
+                    self.channel
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// rustup invocations that set up this spec on a fresh rustup, e.g.
+    /// in a container image
+    pub(crate) fn setup_commands(&self) -> Vec<Vec<String>> {
+        self.commands(&RustupState::default())
+    }
+
+    /// rustup invocations still needed to get from `state` to this spec
+    fn commands(&self, state: &RustupState) -> Vec<Vec<String>> {
+        let channel = self.channel.to_string();
+        let args = |parts: &[&str], rest: &[String]| -> Vec<String> {
+            parts.iter().map(|part| part.to_string()).chain(rest.iter().cloned()).collect()
+        };
+        let mut commands = Vec::new();
+
+        for toolchain in std::iter::once(&self.channel).chain(&self.extra_toolchains) {
+            let name = toolchain.to_string();
+            if !state.toolchains.iter().any(|installed| rustup_name_matches(installed, &name)) {
+                commands.push(args(&["toolchain", "install", &name, "--profile", "minimal"], &[]));
+            }
+        }
+
+        if !state.default.as_deref().is_some_and(|default| rustup_name_matches(default, &channel)) {
+            commands.push(args(&["default", &channel], &[]));
+        }
+
+        let missing = |wanted: &[String], installed: &[String]| -> Vec<String> {
+            wanted
+                .iter()
+                .filter(|name| !installed.iter().any(|i| rustup_name_matches(i, name)))
+                .cloned()
+                .collect()
+        };
+        let components = missing(&self.components, &state.components);
+        if !components.is_empty() {
+            commands.push(args(&["component", "add", "--toolchain", &channel], &components));
+        }
+        let targets = missing(&self.targets, &state.targets);
+        if !targets.is_empty() {
+            commands.push(args(&["target", "add", "--toolchain", &channel], &targets));
+        }
+
+        commands
+    }
+}
+
+/// Whether a name from rustup output refers to `name`
+///
+/// rustup appends the host triple to toolchains and most components,
+/// so `stable` matches `stable-x86_64-unknown-linux-gnu` while
+/// `nightly` must not match `nightly-2024-11-28-x86_64-unknown-linux-gnu`.
+fn rustup_name_matches(installed: &str, name: &str) -> bool {
+    installed == name
+        || installed
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('-'))
+            .is_some_and(|triple| TRIPLE_ARCHES.iter().any(|arch| triple.starts_with(arch)))
+}
+
+/// What rustup currently has installed
+#[derive(Debug, Default)]
+struct RustupState {
+    toolchains: Vec<String>,
+    default: Option<String>,
+    /// Components of the spec's default channel
+    components: Vec<String>,
+    /// Targets of the spec's default channel
+    targets: Vec<String>,
+}
+
+impl RustupState {
+    fn read(channel: &Channel) -> Self {
+        // First word of each line; a missing toolchain just lists nothing
+        let list = |args: &[&str]| -> Vec<String> {
+            Command::new(rustup_bin("rustup"))
+                .args(args)
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| {
+                    String::from_utf8_lossy(&output.stdout)
+                        .lines()
+                        .filter_map(|line| line.split_whitespace().next())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let channel = channel.to_string();
+
+        Self {
+            toolchains: list(&["toolchain", "list"]),
+            default: list(&["default"]).into_iter().next(),
+            components: list(&["component", "list", "--installed", "--toolchain", &channel]),
+            targets: list(&["target", "list", "--installed", "--toolchain", &channel]),
+        }
+    }
+}
+
+/// Directory holding cargo installed binaries
+pub fn cargo_bin_dir() -> Result<PathBuf> {
+    if let Ok(cargo_home) = env::var("CARGO_HOME") {
+        return Ok(PathBuf::from(cargo_home).join("bin"));
+    }
+    let home = home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+    Ok(home.join(".cargo/bin"))
+}
+
+/// Path to a rustup managed binary, falling back to the bare name
+///
+/// rustup is installed with `--no-modify-path`, so freshly installed
+/// binaries are not on this process's PATH yet.
+pub(crate) fn rustup_bin(name: &str) -> String {
+    find_rustup_bin(name, cargo_bin_dir().ok().as_deref(), env::var_os("PATH"))
+        .map_or_else(|| name.to_string(), |path| path.display().to_string())
+}
+
+/// Look for `name` in the cargo bin directory, then on `path`
+///
+/// A rustup from a distro package or another CARGO_HOME is only on PATH.
+fn find_rustup_bin(name: &str, bin_dir: Option<&Path>, path: Option<OsString>) -> Option<PathBuf> {
+    bin_dir
+        .map(|dir| dir.join(name))
+        .filter(|candidate| candidate.exists())
+        .or_else(|| which::which_in(name, path, env::current_dir().ok()?).ok())
+}
+
+/// All steps needed for the Rust toolchain and catalog crates
+pub fn steps(plan: &InstallPlan) -> Vec<Box<dyn Step>> {
+    let mut steps: Vec<Box<dyn Step>> = Vec::new();
+    if plan.installs_system_packages() {
+        steps.push(Box::new(SystemPackages::new(
+            "rust.base-packages",
+            COMPONENT,
+            catalog::get_many(&["build-essential"]),
+            plan.privilege,
+        )));
+    }
+    steps.push(Box::new(Rustup { channel: plan.rust.channel.clone() }));
+    steps.push(Box::new(RustToolchain { spec: plan.rust.clone() }));
+
+    if let Ok(dir) = cargo_bin_dir() {
+        steps.push(Box::new(PathEntry::new("rust.path", COMPONENT, dir)));
+    }
+
+    steps.push(Box::new(CargoBinstall));
+    let package_steps: Vec<String> = plan.components.iter().map(SystemPackages::id_for).collect();
+    // Preflight reports a bad override, so only --skip-preflight gets here
+    match configured_crates() {
+        Ok(crates) => {
+            for spec in crates {
+                steps.push(Box::new(CargoCrate { spec, package_steps: package_steps.clone() }));
+            }
+        }
+        Err(e) => logging::error(format!("Skipping cargo crates: {:#}", e)),
+    }
+
+    steps
+}
+
+/// Install rustup with rustup-init
+///
+/// An existing rustup, in the cargo bin directory or on PATH, is left
+/// alone; `RustToolchain` brings it in line with the spec.
+pub struct Rustup {
+    channel: Channel,
+}
+
+impl Step for Rustup {
+    fn id(&self) -> String {
+        "rust.rustup".to_string()
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        "Install rustup".to_string()
+    }
+
+    fn check(&self) -> Result<bool> {
+        Ok(find_rustup_bin("rustup", cargo_bin_dir().ok().as_deref(), env::var_os("PATH")).is_some())
+    }
+
+    fn apply(&self, _notes: &mut StepNotes) -> Result<()> {
+        let installer = env::temp_dir().join("rustup-init.sh");
+        let installer = installer.to_str().ok_or_else(|| anyhow!("Invalid temp path"))?;
+
+        run_cmd(
+            "curl",
+            &["--proto", "=https", "--tlsv1.2", "-sSf", RUSTUP_INSTALL_URL, "-o", installer],
+        )
+        .context("Failed to download rustup")?;
+
+        run_cmd("chmod", &["+x", installer])?;
+        let channel = self.channel.to_string();
+        run_cmd(
+            installer,
+            &["-y", "--no-modify-path", "--profile", "minimal", "--default-toolchain", &channel],
+        )
+        .context("Failed to run rustup installer")?;
+        Ok(())
+    }
+}
+
+/// Install the toolchains, components and targets of a `RustSpec`
+pub struct RustToolchain {
+    spec: RustSpec,
+}
+
+impl Step for RustToolchain {
+    fn id(&self) -> String {
+        "rust.toolchain".to_string()
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        format!("Set up the {} Rust toolchain", self.spec.channel)
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["rust.rustup".to_string()]
+    }
+
+    fn check(&self) -> Result<bool> {
+        self.spec.validate()?;
+        Ok(self.spec.commands(&RustupState::read(&self.spec.channel)).is_empty())
+    }
+
+    fn apply(&self, _notes: &mut StepNotes) -> Result<()> {
+        self.spec.validate()?;
+        let rustup = rustup_bin("rustup");
+        for command in self.spec.commands(&RustupState::read(&self.spec.channel)) {
+            let args: Vec<&str> = command.iter().map(String::as_str).collect();
+            run_cmd(&rustup, &args).with_context(|| format!("Failed to run rustup {}", command.join(" ")))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_rustup_bin_falls_back_to_path() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = tempfile::tempdir().unwrap();
+        let cargo_bin = root.path().join("cargo/bin");
+        let system_bin = root.path().join("usr/bin");
+        std::fs::create_dir_all(&cargo_bin).unwrap();
+        std::fs::create_dir_all(&system_bin).unwrap();
+        let distro_rustup = system_bin.join("rustup");
+        std::fs::write(&distro_rustup, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&distro_rustup, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let path = Some(system_bin.clone().into_os_string());
+
+        assert_eq!(find_rustup_bin("rustup", Some(&cargo_bin), path.clone()), Some(distro_rustup));
+        std::fs::write(cargo_bin.join("rustup"), "").unwrap();
+        assert_eq!(find_rustup_bin("rustup", Some(&cargo_bin), path.clone()), Some(cargo_bin.join("rustup")));
+        assert_eq!(find_rustup_bin("cargo", Some(&cargo_bin), path), None);
+    }
+
+    #[test]
+    fn test_channel_parsing() {
+        assert_eq!("nightly".parse::<Channel>().unwrap(), Channel::Nightly);
+        assert_eq!(
+            "nightly-2024-11-28".parse::<Channel>().unwrap(),
+            Channel::Pinned("nightly-2024-11-28".to_string())
+        );
+        assert_eq!("1.82.0".parse::<Channel>().unwrap(), Channel::Pinned("1.82.0".to_string()));
+        assert!("nightly-latest".parse::<Channel>().is_err());
+        assert!("1.82.x".parse::<Channel>().is_err());
+
+        let spec = RustSpec {
+            components: vec!["miri".to_string()],
+            ..RustSpec::default()
+        };
+        assert!(spec.validate().is_err());
+        assert!(RustSpec { channel: Channel::Nightly, ..spec }.validate().is_ok());
+    }
+
+    #[test]
+    fn test_commands_only_cover_what_is_missing() {
+        let spec = RustSpec {
+            channel: Channel::Nightly,
+            extra_toolchains: vec![Channel::Stable],
+            components: vec!["clippy".to_string(), "rust-src".to_string()],
+            targets: vec!["wasm32-unknown-unknown".to_string()],
+        };
+        let state = RustupState {
+            toolchains: vec![
+                "stable-x86_64-unknown-linux-gnu".to_string(),
+                "nightly-2024-11-28-x86_64-unknown-linux-gnu".to_string(),
+            ],
+            default: Some("stable-x86_64-unknown-linux-gnu".to_string()),
+            components: vec![],
+            targets: vec![],
+        };
+        let commands: Vec<String> = spec.commands(&state).iter().map(|c| c.join(" ")).collect();
+        assert_eq!(
+            commands,
+            vec![
+                "toolchain install nightly --profile minimal",
+                "default nightly",
+                "component add --toolchain nightly clippy rust-src",
+                "target add --toolchain nightly wasm32-unknown-unknown",
+            ]
+        );
+
+        let state = RustupState {
+            toolchains: vec![
+                "stable-x86_64-unknown-linux-gnu".to_string(),
+                "nightly-x86_64-unknown-linux-gnu".to_string(),
+            ],
+            default: Some("nightly-x86_64-unknown-linux-gnu".to_string()),
+            components: vec!["clippy-x86_64-unknown-linux-gnu".to_string(), "rust-src".to_string()],
+            targets: vec!["wasm32-unknown-unknown".to_string()],
+        };
+        assert!(spec.commands(&state).is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::{anyhow, Result};
+use dirs::home_dir;
+use std::fs;
+use std::path::PathBuf;
+
+use super::{Lock, Step, StepNotes};
+use crate::{gpu, logging, setcyrup};
+
+const COMPONENT: &str = "Setup";
+
+fn config_dir() -> Result<PathBuf> {
+    let home = home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+    Ok(home.join(".config/cyrup"))
+}
+
+/// Create `~/.config/cyrup`
+pub struct ConfigDir;
+
+impl Step for ConfigDir {
+    fn id(&self) -> String {
+        "setup.config-dir".to_string()
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        "Create config directory".to_string()
+    }
+
+    fn check(&self) -> Result<bool> {
+        Ok(config_dir()?.is_dir())
+    }
+
+    fn apply(&self, _notes: &mut StepNotes) -> Result<()> {
+        fs::create_dir_all(config_dir()?)?;
+        Ok(())
+    }
+}
+
+/// Write the Cyrup shell config and source it from the user's rc files
+pub struct Shell;
+
+impl Step for Shell {
+    fn id(&self) -> String {
+        "setup.shell".to_string()
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        "Configure shell".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["setup.config-dir".to_string()]
+    }
+
+    fn locks(&self) -> Vec<Lock> {
+        vec![Lock::ShellRc]
+    }
+
+    fn check(&self) -> Result<bool> {
+        setcyrup::shell_configured()
+    }
+
+    fn apply(&self, notes: &mut StepNotes) -> Result<()> {
+        setcyrup::configure_shell()?;
+        notes
+            .follow_ups
+            .push("Restart your terminal to pick up the new shell configuration".to_string());
+        Ok(())
+    }
+}
+
+/// Check GPU availability
+///
+/// Never satisfied up front, so the summary always carries the current
+/// GPU warnings.
+pub struct Gpu;
+
+impl Step for Gpu {
+    fn id(&self) -> String {
+        "setup.gpu".to_string()
+    }
+
+    fn component(&self) -> &str {
+        COMPONENT
+    }
+
+    fn name(&self) -> String {
+        "Detect GPU".to_string()
+    }
+
+    fn check(&self) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn apply(&self, notes: &mut StepNotes) -> Result<()> {
+        for warning in gpu::detect_and_setup()? {
+            logging::warn(&warning);
+            notes.warnings.push(warning);
+        }
+        Ok(())
+    }
+
+    fn verify(&self) -> Result<bool> {
+        Ok(true)
+    }
+}
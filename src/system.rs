@@ -1,15 +1,30 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use std::process::Command;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use crate::logging;
+
+/// Run a command, capturing its output into the run log
 pub fn run_cmd(cmd: &str, args: &[&str]) -> Result<()> {
-    let status = Command::new(cmd)
+    let started = Instant::now();
+    let output = Command::new(cmd)
         .args(args)
-        .status()?;
+        .output()
+        .with_context(|| format!("Failed to execute command: {} {:?}", cmd, args))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    logging::command(cmd, args, output.status.code(), started.elapsed(), &stdout, &stderr);
 
-    if !status.success() {
-        return Err(anyhow!("Command failed: {} {:?}", cmd, args));
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Command failed with {}: {} {:?}: {}",
+            output.status,
+            cmd,
+            args,
+            stderr.trim()
+        ));
     }
 
     Ok(())
@@ -1,19 +1,32 @@
 use anyhow::Result;
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Gauge, Paragraph},
+    widgets::{Block, Borders, Gauge, Paragraph, Wrap},
 };
 use std::io::{self, Stdout};
+use std::time::Instant;
 use crossterm::{
+    event::{self, Event, KeyEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::backend::CrosstermBackend;
 
+use crate::report::{format_duration, InstallReport, StepStatus};
+
+/// A step currently running on a worker
+struct RunningTask {
+    id: String,
+    component: String,
+    name: String,
+    started: Instant,
+}
+
 pub struct Installer {
     terminal: Terminal<CrosstermBackend<Stdout>>,
     progress: f64,
     status: String,
+    running: Vec<RunningTask>,
 }
 
 impl Installer {
@@ -28,6 +41,7 @@ impl Installer {
             terminal,
             progress: 0.0,
             status: String::from("Starting installation..."),
+            running: Vec::new(),
         })
     }
 
@@ -48,7 +62,81 @@ impl Installer {
         Ok(())
     }
 
+    /// Show a step as running alongside any others
+    pub fn start_task(
+        &mut self,
+        id: impl Into<String>,
+        component: impl Into<String>,
+        name: impl Into<String>,
+    ) -> Result<()> {
+        self.running.push(RunningTask {
+            id: id.into(),
+            component: component.into(),
+            name: name.into(),
+            started: Instant::now(),
+        });
+        self.draw()
+    }
+
+    /// Stop showing a step as running, redrawn with the next update
+    pub fn finish_task(&mut self, id: &str) {
+        self.running.retain(|task| task.id != id);
+    }
+
+    /// Redraw, e.g. to refresh elapsed times of running tasks
+    pub fn redraw(&mut self) -> Result<()> {
+        self.draw()
+    }
+
+    /// Show the final summary and wait until a key is pressed
+    pub fn show_summary(&mut self, report: &InstallReport) -> Result<()> {
+        let lines = summary_lines(report);
+
+        self.terminal.draw(|frame| {
+            let area = frame.area();
+
+            let title = if report.has_failures() {
+                "Cyrup Installer - Installation failed"
+            } else {
+                "Cyrup Installer - Installation complete"
+            };
+            let block = Block::default().borders(Borders::ALL).title(title);
+
+            let inner_area = block.inner(area);
+            frame.render_widget(block, area);
+
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .margin(1)
+                .constraints([
+                    Constraint::Min(1),    // Summary
+                    Constraint::Length(1), // Help
+                ].as_ref())
+                .split(inner_area);
+
+            let summary = Paragraph::new(lines).wrap(Wrap { trim: false });
+            frame.render_widget(summary, chunks[0]);
+
+            let help = Paragraph::new("Press any key to exit")
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::DarkGray));
+            frame.render_widget(help, chunks[1]);
+        })?;
+
+        loop {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
     fn draw(&mut self) -> Result<()> {
+        let running = &self.running;
+        let progress = self.progress;
+        let status = self.status.as_str();
+
         self.terminal.draw(|frame| {
             let area = frame.area();
             
@@ -67,6 +155,10 @@ impl Installer {
                 .constraints([
                     Constraint::Length(1), // Progress bar
                     Constraint::Length(1), // Status
+                    Constraint::Length(1), // Spacer
+                    Constraint::Length(5), // Running tasks
+                    Constraint::Min(0),
+                    Constraint::Length(1), // Help
                 ].as_ref())
                 .split(inner_area);
 
@@ -74,22 +166,41 @@ impl Installer {
             let gauge = Gauge::default()
                 .block(Block::default())
                 .gauge_style(Style::default().fg(Color::Green))
-                .ratio(self.progress);
+                .ratio(progress);
             frame.render_widget(gauge, chunks[0]);
 
             // Render status text
-            let status = Paragraph::new(self.status.as_str())
+            let status = Paragraph::new(status)
                 .style(Style::default().fg(Color::Yellow));
             frame.render_widget(status, chunks[1]);
 
+            // Render running tasks side by side
+            if !running.is_empty() {
+                let columns = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints(vec![Constraint::Ratio(1, running.len() as u32); running.len()])
+                    .split(chunks[3]);
+
+                for (task, column) in running.iter().zip(columns.iter()) {
+                    let text = vec![
+                        Line::raw(task.name.clone()),
+                        Line::styled(
+                            format_duration(task.started.elapsed()),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                    ];
+                    let panel = Paragraph::new(text)
+                        .wrap(Wrap { trim: true })
+                        .block(Block::default().borders(Borders::ALL).title(task.component.clone()));
+                    frame.render_widget(panel, *column);
+                }
+            }
+
             // Render help text at bottom
             let help = Paragraph::new("Press Ctrl+C to cancel")
                 .alignment(Alignment::Center)
                 .style(Style::default().fg(Color::DarkGray));
-            frame.render_widget(help, inner_area.inner(Margin { 
-                vertical: chunks[1].bottom() + 1,
-                horizontal: 0 
-            }));
+            frame.render_widget(help, chunks[5]);
         })?;
 
         Ok(())
@@ -105,3 +216,68 @@ impl Drop for Installer {
         );
     }
 }
+
+/// Build the lines shown on the summary screen
+fn summary_lines(report: &InstallReport) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut component = None;
+
+    for step in &report.steps {
+        if component != Some(&step.component) {
+            if component.is_some() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::styled(step.component.clone(), Style::default().bold()));
+            component = Some(&step.component);
+        }
+
+        let (symbol, color, detail) = match &step.status {
+            StepStatus::Success => ("✔", Color::Green, step.detail.clone()),
+            StepStatus::Failed(e) => ("✘", Color::Red, Some(e.clone())),
+            StepStatus::Skipped(reason) => ("-", Color::DarkGray, Some(reason.clone())),
+        };
+
+        let mut spans = vec![
+            Span::styled(format!("  {} ", symbol), Style::default().fg(color)),
+            Span::raw(step.name.clone()),
+            Span::styled(
+                format!(" ({})", format_duration(step.duration)),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ];
+        if let Some(detail) = detail {
+            spans.push(Span::styled(format!(" - {}", detail), Style::default().fg(color)));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    if !report.warnings.is_empty() {
+        lines.push(Line::default());
+        lines.push(Line::styled("Warnings", Style::default().bold().fg(Color::Yellow)));
+        for warning in &report.warnings {
+            lines.push(Line::styled(format!("  ⚠ {}", warning), Style::default().fg(Color::Yellow)));
+        }
+    }
+
+    if !report.follow_ups.is_empty() {
+        lines.push(Line::default());
+        lines.push(Line::styled("Next steps", Style::default().bold().fg(Color::Cyan)));
+        for action in &report.follow_ups {
+            lines.push(Line::raw(format!("  → {}", action)));
+        }
+    }
+
+    lines.push(Line::default());
+    lines.push(Line::styled(
+        format!("Total time: {}", format_duration(report.elapsed())),
+        Style::default().fg(Color::DarkGray),
+    ));
+    if let Some(path) = &report.log_path {
+        lines.push(Line::styled(
+            format!("Full log: {}", path.display()),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    lines
+}